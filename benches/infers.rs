@@ -1,5 +1,5 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use infers_jsonschema::inference::infer;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use infers_jsonschema::{infer, JSONSchema};
 use serde_json::{from_str, json, Value};
 use std::fs::File;
 use std::io::Read;
@@ -7,7 +7,7 @@ use std::path::Path;
 
 fn read_json(filepath: &str) -> Value {
     let path = Path::new(filepath);
-    let mut file = File::open(&path).unwrap();
+    let mut file = File::open(path).unwrap();
     let mut content = String::new();
     file.read_to_string(&mut content).ok().unwrap();
     let data: Value = from_str(&content).unwrap();
@@ -20,6 +20,44 @@ fn canada_benchmark(c: &mut Criterion) {
     c.bench_function("canada bench", |b| b.iter(|| infer(&data)));
 }
 
-criterion_group!(benches, canada_benchmark);
+fn parallel_threshold_benchmark(c: &mut Criterion) {
+    let scalars = black_box(Value::Array((0..64).map(|idx| json!(idx)).collect()));
+    let nested = black_box(Value::Array(
+        (0..64)
+            .map(|idx| json!({"id": idx, "tags": ["a", "b"], "meta": {"values": [1, 2.5, null]}}))
+            .collect(),
+    ));
+    let mut group = c.benchmark_group("parallel threshold");
+    for threshold in &[4, 8, 64] {
+        group.bench_with_input(
+            BenchmarkId::new("scalars", threshold),
+            threshold,
+            |b, &t| b.iter(|| JSONSchema::new(&scalars).parallel_threshold(t).infer()),
+        );
+        group.bench_with_input(BenchmarkId::new("nested", threshold), threshold, |b, &t| {
+            b.iter(|| JSONSchema::new(&nested).parallel_threshold(t).infer())
+        });
+    }
+    group.finish();
+}
+
+fn identical_records_benchmark(c: &mut Criterion) {
+    let record = json!({"id": 1, "name": "foo", "tags": ["a", "b"], "meta": {"values": [1, 2.5]}});
+    let data = black_box(Value::Array(vec![record; 10_000]));
+    c.bench_function("identical records", |b| b.iter(|| infer(&data)));
+}
+
+fn homogeneous_scalars_benchmark(c: &mut Criterion) {
+    let data = black_box(Value::Array((0..100_000).map(|idx| json!(idx)).collect()));
+    c.bench_function("homogeneous integers", |b| b.iter(|| infer(&data)));
+}
+
+criterion_group!(
+    benches,
+    canada_benchmark,
+    parallel_threshold_benchmark,
+    identical_records_benchmark,
+    homogeneous_scalars_benchmark
+);
 
 criterion_main!(benches);
@@ -0,0 +1,410 @@
+//! Translation of inferred schemas to Avro schemas.
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use serde_json::{json, Map, Value};
+
+/// Avro has no type that accepts any value, so schemas without a type become a union of all
+/// primitive types
+const ANY: &[&str] = &["null", "boolean", "long", "double", "string"];
+
+/// Translate an inferred JSON Schema to an Avro schema.
+///
+/// Objects with properties become records, objects that are described by `additionalProperties`
+/// become maps and arrays become arrays. Unions, including arrays of types and optional
+/// properties, become Avro unions with `null` first, so nullable fields default to `null`.
+/// Names of fields and records are sanitized to Avro identifiers, e.g. `user-id` becomes
+/// `user_id`. Formats are not translated, as Avro logical types need converted values.
+///
+/// ```rust
+/// use infers_jsonschema::{infer, to_avro};
+/// use serde_json::json;
+///
+/// let schema = infer(&json!({"id": 1, "tags": ["a"]}));
+/// assert_eq!(
+///     to_avro(&schema),
+///     json!({
+///         "type": "record",
+///         "name": "Root",
+///         "fields": [
+///             {"name": "id", "type": "long"},
+///             {"name": "tags", "type": {"type": "array", "items": "string"}}
+///         ]
+///     })
+/// );
+/// ```
+pub fn to_avro(schema: &Value) -> Value {
+    // The root may be referenced by recursive schemas
+    Translator {
+        root: schema,
+        names: BTreeSet::new(),
+        references: BTreeMap::new(),
+    }
+    .translate_reference("#", "Root")
+}
+
+struct Translator<'a> {
+    root: &'a Value,
+    /// Record and enum names that are already used, Avro requires them to be unique
+    names: BTreeSet<String>,
+    /// Names of records that were translated from referenced schemas
+    references: BTreeMap<String, String>,
+}
+
+impl Translator<'_> {
+    fn translate(&mut self, schema: &Value, name: &str) -> Value {
+        let map = match schema {
+            Value::Object(map) => map,
+            Value::Bool(false) => return json!("null"),
+            _ => return any(),
+        };
+        if let Some(Value::String(reference)) = map.get("$ref") {
+            return self.translate_reference(reference, name);
+        }
+        if let Some(Value::Array(branches)) = map.get("anyOf").or_else(|| map.get("oneOf")) {
+            let branches: Vec<Value> = branches
+                .iter()
+                .map(|branch| self.translate(branch, name))
+                .collect();
+            return union(branches);
+        }
+        match map.get("type") {
+            Some(Value::String(type_)) => self.translate_type(map, type_, name),
+            Some(Value::Array(types)) => {
+                let branches: Vec<Value> = types
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|type_| self.translate_type(map, type_, name))
+                    .collect();
+                union(branches)
+            }
+            _ => any(),
+        }
+    }
+
+    fn translate_type(&mut self, map: &Map<String, Value>, type_: &str, name: &str) -> Value {
+        match type_ {
+            "null" => json!("null"),
+            "boolean" => json!("boolean"),
+            "integer" => json!("long"),
+            "number" => json!("double"),
+            "string" => self.translate_string(map, name),
+            "array" => {
+                let items = match map.get("items") {
+                    Some(items) if !items.is_array() => {
+                        self.translate(items, &format!("{}_item", name))
+                    }
+                    _ => any(),
+                };
+                json!({"type": "array", "items": items})
+            }
+            _ => self.translate_object(map, name),
+        }
+    }
+
+    /// Strings with enumerated values that are all valid Avro names become enums
+    fn translate_string(&mut self, map: &Map<String, Value>, name: &str) -> Value {
+        if let Some(Value::Array(values)) = map.get("enum") {
+            let symbols: Option<Vec<&str>> = values
+                .iter()
+                .map(|value| value.as_str().filter(|symbol| is_name(symbol)))
+                .collect();
+            if let Some(symbols) = symbols {
+                let name = self.unique_name(name);
+                return json!({"type": "enum", "name": name, "symbols": symbols});
+            }
+        }
+        json!("string")
+    }
+
+    fn translate_object(&mut self, map: &Map<String, Value>, name: &str) -> Value {
+        let properties = match map.get("properties") {
+            Some(Value::Object(properties)) => properties,
+            _ => {
+                let values = match map.get("additionalProperties") {
+                    Some(values @ Value::Object(_)) => {
+                        self.translate(values, &format!("{}_value", name))
+                    }
+                    _ => any(),
+                };
+                return json!({"type": "map", "values": values});
+            }
+        };
+        let name = self.unique_name(name);
+        self.translate_record(map, properties, name)
+    }
+
+    fn translate_record(
+        &mut self,
+        map: &Map<String, Value>,
+        properties: &Map<String, Value>,
+        name: String,
+    ) -> Value {
+        let required = map.get("required").and_then(Value::as_array);
+        let mut field_names = BTreeSet::new();
+        let mut fields = Vec::with_capacity(properties.len());
+        for (property, schema) in properties {
+            let mut field_name = sanitize(property);
+            let base = field_name.clone();
+            let mut suffix = 1;
+            while !field_names.insert(field_name.clone()) {
+                suffix += 1;
+                field_name = format!("{}_{}", base, suffix);
+            }
+            let mut type_ = self.translate(schema, &format!("{}_{}", name, field_name));
+            let is_required = required
+                .is_some_and(|required| required.iter().any(|required| required == property));
+            if !is_required {
+                type_ = union(vec![json!("null"), type_]);
+            }
+            let mut field = Map::new();
+            field.insert("name".into(), Value::String(field_name));
+            let nullable = type_.as_array().is_some_and(|types| types[0] == "null");
+            field.insert("type".into(), type_);
+            if nullable {
+                field.insert("default".into(), Value::Null);
+            }
+            fields.push(Value::Object(field));
+        }
+        json!({"type": "record", "name": name, "fields": fields})
+    }
+
+    /// Object schemas that are referenced more than once, e.g. recursive ones, are translated
+    /// once and referred to by their record names
+    fn translate_reference(&mut self, reference: &str, name: &str) -> Value {
+        if let Some(known) = self.references.get(reference) {
+            return Value::String(known.clone());
+        }
+        let root = self.root;
+        let target = match root.pointer(reference.trim_start_matches('#')) {
+            Some(target) => target,
+            None => return any(),
+        };
+        match target.get("properties") {
+            Some(Value::Object(properties)) if target.get("type") == Some(&json!("object")) => {
+                let name = self.unique_name(name);
+                self.references.insert(reference.to_string(), name.clone());
+                let map = target.as_object().expect("Object schema");
+                self.translate_record(map, properties, name)
+            }
+            // References to other schemas are not recursive and are inlined
+            _ => self.translate(target, name),
+        }
+    }
+
+    fn unique_name(&mut self, name: &str) -> String {
+        let base = sanitize(name);
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while !self.names.insert(name.clone()) {
+            suffix += 1;
+            name = format!("{}_{}", base, suffix);
+        }
+        name
+    }
+}
+
+fn any() -> Value {
+    json!(ANY)
+}
+
+/// Join Avro schemas into a union. Nested unions are not allowed in Avro, so they are
+/// flattened, and `null` goes first. A union may have only one array and one map, so arrays are
+/// joined into one array of the union of their items, and maps likewise
+fn union(branches: Vec<Value>) -> Value {
+    let mut flat = Vec::with_capacity(branches.len());
+    for branch in branches {
+        match branch {
+            Value::Array(nested) => flat.extend(nested),
+            branch => flat.push(branch),
+        }
+    }
+    let mut result: Vec<Value> = Vec::with_capacity(flat.len());
+    for branch in flat {
+        if !result.contains(&branch) {
+            result.push(branch);
+        }
+    }
+    join_same_kind(&mut result, "array", "items");
+    join_same_kind(&mut result, "map", "values");
+    if let Some(position) = result.iter().position(|branch| branch == "null") {
+        let null = result.remove(position);
+        result.insert(0, null);
+    }
+    if result.len() == 1 {
+        result.swap_remove(0)
+    } else {
+        Value::Array(result)
+    }
+}
+
+/// Replace all branches of the given unnamed complex type by one branch, whose nested schema
+/// under `keyword` is the union of their nested schemas
+fn join_same_kind(branches: &mut Vec<Value>, type_: &str, keyword: &str) {
+    let is_kind = |branch: &Value| branch.get("type").is_some_and(|kind| kind == type_);
+    let first = match branches.iter().position(is_kind) {
+        Some(first) if branches.iter().filter(|branch| is_kind(branch)).count() > 1 => first,
+        _ => return,
+    };
+    let mut nested = vec![];
+    let mut others = Vec::with_capacity(branches.len());
+    for mut branch in branches.drain(..) {
+        if is_kind(&branch) {
+            nested.push(branch[keyword].take());
+        } else {
+            others.push(branch);
+        }
+    }
+    others.insert(first, json!({"type": type_, keyword: union(nested)}));
+    *branches = others;
+}
+
+/// Avro names start with a letter or an underscore, followed by letters, digits and underscores
+fn is_name(string: &str) -> bool {
+    string.starts_with(|char: char| char.is_ascii_alphabetic() || char == '_')
+        && string
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+/// Replace characters that are not allowed in Avro names with underscores
+fn sanitize(string: &str) -> String {
+    let mut name: String = string
+        .chars()
+        .map(|char| {
+            if char.is_ascii_alphanumeric() {
+                char
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !name.starts_with(|char: char| char.is_ascii_alphabetic() || char == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infer, JSONSchema};
+
+    #[test]
+    fn test_object() {
+        let schema = infer(&json!({
+            "id": 1,
+            "score": 1.5,
+            "user-name": "foo",
+            "1st": true,
+            "address": {"city": "bar"}
+        }));
+        assert_eq!(
+            to_avro(&schema),
+            json!({
+                "type": "record",
+                "name": "Root",
+                "fields": [
+                    {"name": "_1st", "type": "boolean"},
+                    {
+                        "name": "address",
+                        "type": {
+                            "type": "record",
+                            "name": "Root_address",
+                            "fields": [{"name": "city", "type": "string"}]
+                        }
+                    },
+                    {"name": "id", "type": "long"},
+                    {"name": "score", "type": "double"},
+                    {"name": "user_name", "type": "string"}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        let schema = json!({"type": "object", "additionalProperties": {"type": "integer"}});
+        assert_eq!(to_avro(&schema), json!({"type": "map", "values": "long"}));
+        assert_eq!(
+            to_avro(&json!({"type": "object"})),
+            json!({"type": "map", "values": ANY})
+        );
+    }
+
+    #[test]
+    fn test_nullable() {
+        let schema = infer(&json!([{"a": 1, "b": true}, {"a": null}]));
+        assert_eq!(
+            to_avro(&schema),
+            json!({
+                "type": "array",
+                "items": {
+                    "type": "record",
+                    "name": "Root_item",
+                    "fields": [
+                        {"name": "a", "type": ["null", "long"], "default": null},
+                        {"name": "b", "type": ["null", "boolean"], "default": null}
+                    ]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_union_of_arrays_and_maps() {
+        let schema = json!({
+            "anyOf": [
+                {"type": "array", "items": {"type": "integer"}},
+                {"type": "array", "items": {"type": "string"}},
+                {"type": "object", "additionalProperties": {"type": "integer"}},
+                {"type": "object", "additionalProperties": {"type": "null"}},
+                {"type": "null"}
+            ]
+        });
+        assert_eq!(
+            to_avro(&schema),
+            json!([
+                "null",
+                {"type": "array", "items": ["long", "string"]},
+                {"type": "map", "values": ["null", "long"]}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert!(is_name("_a1"));
+        assert!(!is_name("1a"));
+        assert!(!is_name(""));
+        assert_eq!(sanitize(""), "_");
+        assert_eq!(sanitize("a.b"), "a_b");
+        let data = json!({"a-b": 1, "a_b": "foo"});
+        let schema = JSONSchema::new(&data).infer();
+        let fields = to_avro(&schema)["fields"].clone();
+        assert_eq!(fields[0]["name"], "a_b");
+        assert_eq!(fields[1]["name"], "a_b_2");
+    }
+
+    #[test]
+    fn test_recursive() {
+        let data = json!({"value": 1, "child": {"value": 2, "child": {"value": 3}}});
+        let schema = JSONSchema::new(&data).detect_recursion(true).infer();
+        // The recursive reference refers to the record by its name
+        assert_eq!(
+            to_avro(&schema),
+            json!({
+                "type": "record",
+                "name": "Root",
+                "fields": [
+                    {"name": "child", "type": ["null", "Root"], "default": null},
+                    {"name": "value", "type": "long"}
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Base64 encoding of binary data from binary input formats.
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode data as standard padded base64
+pub(crate) fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let buffer = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let indices = [
+            buffer[0] >> 2,
+            (buffer[0] & 0b11) << 4 | buffer[1] >> 4,
+            (buffer[1] & 0b1111) << 2 | buffer[2] >> 6,
+            buffer[2] & 0b11_1111,
+        ];
+        for (position, &index) in indices.iter().enumerate() {
+            if position <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[index as usize] as char)
+            } else {
+                encoded.push('=')
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"hello"), "aGVsbG8=");
+    }
+}
@@ -0,0 +1,74 @@
+//! Incremental inference from a stream of samples.
+use crate::{refine::refine_schema, set_schema_uri, Draft, JSONSchema};
+use serde_json::Value;
+
+/// Infers a schema from samples added one by one, so they don't have to be collected first.
+/// The result describes all added samples, as if they were items of one array, so samples of
+/// different types are described by `anyOf` at the root.
+///
+/// ```rust
+/// use infers_jsonschema::SchemaBuilder;
+/// use serde_json::json;
+///
+/// let mut builder = SchemaBuilder::new();
+/// builder.add(&json!({"a": 1}));
+/// builder.add(&json!({"a": 2.5, "b": "foo"}));
+/// assert_eq!(
+///     builder.finish(),
+///     json!({
+///         "type": "object",
+///         "properties": {"a": {"type": "number"}, "b": {"type": "string"}},
+///         "required": ["a"],
+///         "$schema": "http://json-schema.org/draft-07/schema#"
+///     })
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct SchemaBuilder {
+    schema: Option<Value>,
+}
+
+impl SchemaBuilder {
+    pub fn new() -> SchemaBuilder {
+        SchemaBuilder::default()
+    }
+
+    /// Widen the schema so it also describes the sample
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sample can't be described by a schema. See `JSONSchema::try_infer`
+    pub fn add(&mut self, sample: &Value) {
+        let mut inferred = JSONSchema::new(sample).infer();
+        if let Value::Object(map) = &mut inferred {
+            map.remove("$schema");
+        }
+        self.schema = Some(match &self.schema {
+            Some(schema) => refine_schema(schema, &inferred),
+            None => inferred,
+        });
+    }
+
+    /// Get the schema for all added samples. Without samples, it accepts any value
+    pub fn finish(self) -> Value {
+        let mut schema = self
+            .schema
+            .unwrap_or_else(|| Value::Object(Default::default()));
+        set_schema_uri(&mut schema, Draft::Draft7.uri().into());
+        schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(
+            SchemaBuilder::new().finish(),
+            json!({"$schema": "http://json-schema.org/draft-07/schema#"})
+        );
+    }
+}
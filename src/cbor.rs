@@ -0,0 +1,156 @@
+//! Inference from CBOR documents.
+use crate::{base64::base64, escape_token, InferError, JSONSchema};
+use serde_cbor::Value as CborValue;
+use serde_json::{Map, Number, Value};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+/// Infer a schema for a CBOR document with default settings.
+/// Byte strings are described as strings with `contentEncoding: base64`
+pub fn infer_cbor(input: &[u8]) -> Result<Value, InferError> {
+    let document: CborValue =
+        serde_cbor::from_slice(input).map_err(|error| InferError::InvalidInput {
+            message: error.to_string(),
+        })?;
+    let mut bytes = HashSet::new();
+    let converted = convert(document, String::new(), &mut bytes)?;
+    let schema = JSONSchema::new(&converted)
+        .hook(|schema, context| {
+            if bytes.contains(context.pointer()) {
+                schema["contentEncoding"] = Value::String("base64".into());
+            }
+        })
+        .try_infer();
+    schema
+}
+
+/// Convert a CBOR value to JSON, collecting pointers to byte strings
+fn convert(
+    value: CborValue,
+    pointer: String,
+    bytes: &mut HashSet<String>,
+) -> Result<Value, InferError> {
+    Ok(match value {
+        CborValue::Null => Value::Null,
+        CborValue::Bool(boolean) => Value::Bool(boolean),
+        CborValue::Integer(integer) => {
+            if let Ok(integer) = i64::try_from(integer) {
+                Value::Number(integer.into())
+            } else if let Ok(integer) = u64::try_from(integer) {
+                Value::Number(integer.into())
+            } else {
+                // Out of the range of JSON numbers supported by `serde_json`
+                float(integer as f64, pointer)?
+            }
+        }
+        CborValue::Float(number) => float(number, pointer)?,
+        CborValue::Text(text) => Value::String(text),
+        CborValue::Bytes(data) => {
+            bytes.insert(pointer);
+            Value::String(base64(&data))
+        }
+        CborValue::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(idx, item)| convert(item, format!("{}/{}", pointer, idx), bytes))
+                .collect::<Result<_, _>>()?,
+        ),
+        CborValue::Map(entries) => {
+            let mut map = Map::new();
+            for (key, value) in entries {
+                let key = match key {
+                    CborValue::Text(text) => text,
+                    CborValue::Integer(integer) => integer.to_string(),
+                    CborValue::Bool(boolean) => boolean.to_string(),
+                    _ => {
+                        return Err(InferError::InvalidInput {
+                            message: format!("Unsupported map key at '{}'", pointer),
+                        })
+                    }
+                };
+                let location = format!("{}/{}", pointer, escape_token(&key));
+                map.insert(key, convert(value, location, bytes)?);
+            }
+            Value::Object(map)
+        }
+        CborValue::Tag(_, value) => convert(*value, pointer, bytes)?,
+        _ => {
+            return Err(InferError::InvalidInput {
+                message: format!("Unsupported value at '{}'", pointer),
+            })
+        }
+    })
+}
+
+fn float(number: f64, pointer: String) -> Result<Value, InferError> {
+    Number::from_f64(number)
+        .map(Value::Number)
+        .ok_or(InferError::NonFiniteNumber { pointer })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_round_trip() {
+        let document = json!({"id": 1, "name": "foo", "tags": ["a", "b"], "score": 1.5});
+        // With `arbitrary_precision`, `serde_json` numbers are serialized as maps
+        let mut map = BTreeMap::new();
+        map.insert(CborValue::Text("id".into()), CborValue::Integer(1));
+        map.insert(
+            CborValue::Text("name".into()),
+            CborValue::Text("foo".into()),
+        );
+        map.insert(
+            CborValue::Text("tags".into()),
+            CborValue::Array(vec![
+                CborValue::Text("a".into()),
+                CborValue::Text("b".into()),
+            ]),
+        );
+        map.insert(CborValue::Text("score".into()), CborValue::Float(1.5));
+        let encoded = serde_cbor::to_vec(&CborValue::Map(map)).unwrap();
+        assert_eq!(infer_cbor(&encoded), Ok(crate::infer(&document)));
+    }
+
+    #[test]
+    fn test_bytes() {
+        let mut document = BTreeMap::new();
+        document.insert(
+            CborValue::Text("data".into()),
+            CborValue::Bytes(b"hello".to_vec()),
+        );
+        document.insert(
+            CborValue::Text("chunks".into()),
+            CborValue::Array(vec![CborValue::Bytes(vec![1]), CborValue::Bytes(vec![])]),
+        );
+        let encoded = serde_cbor::to_vec(&CborValue::Map(document)).unwrap();
+        let schema = infer_cbor(&encoded).unwrap();
+        let bytes = json!({"type": "string", "contentEncoding": "base64"});
+        assert_eq!(schema["properties"]["data"], bytes);
+        assert_eq!(schema["properties"]["chunks"]["items"], bytes);
+    }
+
+    #[test]
+    fn test_non_finite() {
+        let encoded = serde_cbor::to_vec(&vec![1.0, f64::NAN]).unwrap();
+        assert_eq!(
+            infer_cbor(&encoded),
+            Err(InferError::NonFiniteNumber {
+                pointer: "/1".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            infer_cbor(&[0xff, 0x00]),
+            Err(InferError::InvalidInput { .. })
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+//! Inference settings.
+use crate::{
+    random::DEFAULT_SEED, AdditionalProperties, Draft, EmailStrictness, Fallback, Format,
+    FormatMerge, LengthUnit, ObjectMergeStrategy, OpenApiVersion, TypeArrayOrder, UnionOrder,
+};
+use alloc::{string::String, vec, vec::Vec};
+use serde::Deserialize;
+
+/// Inference settings that can be loaded from a file.
+/// Missing fields get their default values, which are described in the corresponding
+/// builder methods of `JSONSchema`.
+///
+/// ```rust
+/// use infers_jsonschema::{Config, JSONSchema};
+/// use serde_json::json;
+///
+/// let config: Config = serde_json::from_value(json!({"draft": "draft4", "emit_required": false}))
+///     .expect("Invalid config");
+/// let data = json!({"a": 1});
+/// assert_eq!(
+///     JSONSchema::with_config(&data, config).infer(),
+///     json!({
+///         "type": "object",
+///         "properties": {"a": {"type": "integer"}},
+///         "$schema": "http://json-schema.org/draft-04/schema#"
+///     })
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub detect_format: bool,
+    pub formats: Vec<Format>,
+    /// Names of formats that are checked before other enabled formats, in this order
+    pub format_priority: Vec<String>,
+    pub format_merge: FormatMerge,
+    pub map_threshold: Option<usize>,
+    pub key_enum_threshold: Option<usize>,
+    pub unify_int_float: bool,
+    pub parallel_threshold: usize,
+    pub emit_property_order: bool,
+    pub emit_property_names: bool,
+    pub detect_defaults: bool,
+    pub enum_threshold: Option<usize>,
+    pub enum_types: Vec<String>,
+    pub type_as_array: bool,
+    pub assume_unsigned: bool,
+    pub assume_positive: bool,
+    pub object_merge_strategy: ObjectMergeStrategy,
+    pub merged_additional_properties: AdditionalProperties,
+    pub draft: Draft,
+    pub schema_uri: Option<String>,
+    /// The maximum number of collected examples, 0 disables them
+    pub examples: usize,
+    pub root_example: bool,
+    pub emit_generator_info: bool,
+    /// The timestamp in the generator info, none if not set
+    pub generated_at: Option<String>,
+    pub open_empty_objects: bool,
+    /// Whether arrays of tagged objects are inferred as `oneOf`
+    pub discriminate: bool,
+    /// The tag property, detected automatically if not set
+    pub discriminator: Option<String>,
+    pub string_length_bounds: bool,
+    pub number_bounds: bool,
+    /// The number of decimal places numeric bounds are rounded outward to, none if not rounded
+    pub bounds_rounding: Option<u32>,
+    pub max_length_cap: Option<usize>,
+    pub length_unit: LengthUnit,
+    pub annotate_provenance: bool,
+    pub emit_required: bool,
+    /// The maximal number of objects containing an object schema with `required`
+    pub required_max_depth: Option<usize>,
+    pub extract_definitions: bool,
+    pub detect_embedded_json: bool,
+    pub case_insensitive_keys: bool,
+    pub detect_recursion: bool,
+    /// JSON Pointers of values that are described by `{}`, `*` matches any reference token
+    pub ignore_paths: Vec<String>,
+    pub strict: bool,
+    pub detect_bool_const: bool,
+    /// The minimal share of array items of one type, rarer types are not described
+    pub item_type_threshold: Option<f64>,
+    /// The maximum number of array items to infer schemas from, larger arrays are sampled.
+    /// 0 disables sampling
+    pub sample_size: Option<usize>,
+    /// The seed of pseudo-random decisions, e.g. which array items are sampled
+    pub seed: u64,
+    pub optional_annotation: bool,
+    pub minimal: bool,
+    pub simplify_unions: bool,
+    pub tuple_arrays: bool,
+    pub set_semantics: bool,
+    pub array_length_bounds: bool,
+    pub max_items_cap: Option<usize>,
+    pub detect_byte_arrays: bool,
+    /// How `anyOf` branches are ordered, by the order of inference if not set
+    pub union_order: Option<UnionOrder>,
+    /// How array items of too many different schemas are described
+    pub array_items_fallback: Fallback,
+    /// The maximal number of `anyOf` branches, larger unions are described only by their types
+    pub max_union_branches: Option<usize>,
+    /// How unions of bare types are collapsed into ordered arrays of types, kept as `anyOf` if not set
+    pub type_array_order: Option<TypeArrayOrder>,
+    /// Names of value types that make inference fail
+    pub forbidden_types: Vec<String>,
+    pub require_composite_root: bool,
+    pub email_validation: EmailStrictness,
+    /// The OpenAPI version whose schema dialect is produced instead of plain JSON Schema
+    pub openapi: Option<OpenApiVersion>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            detect_format: true,
+            formats: Format::DEFAULT.to_vec(),
+            format_priority: vec![],
+            format_merge: FormatMerge::Intersect,
+            map_threshold: None,
+            key_enum_threshold: None,
+            unify_int_float: false,
+            parallel_threshold: 8,
+            emit_property_order: false,
+            emit_property_names: false,
+            detect_defaults: false,
+            enum_threshold: None,
+            enum_types: vec!["string".into(), "integer".into(), "number".into()],
+            type_as_array: false,
+            assume_unsigned: false,
+            assume_positive: false,
+            object_merge_strategy: ObjectMergeStrategy::Merge,
+            merged_additional_properties: AdditionalProperties::Allow,
+            draft: Draft::Draft7,
+            schema_uri: None,
+            examples: 0,
+            root_example: false,
+            emit_generator_info: false,
+            generated_at: None,
+            open_empty_objects: false,
+            discriminate: false,
+            discriminator: None,
+            string_length_bounds: false,
+            number_bounds: false,
+            bounds_rounding: None,
+            max_length_cap: None,
+            length_unit: LengthUnit::Chars,
+            annotate_provenance: false,
+            emit_required: true,
+            required_max_depth: None,
+            extract_definitions: false,
+            detect_embedded_json: false,
+            case_insensitive_keys: false,
+            detect_recursion: false,
+            ignore_paths: vec![],
+            strict: false,
+            detect_bool_const: false,
+            item_type_threshold: None,
+            sample_size: None,
+            seed: DEFAULT_SEED,
+            optional_annotation: false,
+            minimal: false,
+            simplify_unions: true,
+            tuple_arrays: false,
+            set_semantics: false,
+            array_length_bounds: false,
+            max_items_cap: None,
+            detect_byte_arrays: false,
+            union_order: None,
+            array_items_fallback: Fallback::AnyOf,
+            max_union_branches: None,
+            type_array_order: None,
+            forbidden_types: vec![],
+            require_composite_root: false,
+            email_validation: EmailStrictness::Strict,
+            openapi: None,
+        }
+    }
+}
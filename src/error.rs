@@ -0,0 +1,80 @@
+//! Inference errors.
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// An error that happened during inference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InferError {
+    /// A number that is not finite, e.g. NaN or infinity.
+    /// JSON has no such numbers and JSON Schema can't describe them
+    NonFiniteNumber {
+        /// JSON Pointer to the number in the input document
+        pointer: String,
+    },
+    /// The input document can't be decoded
+    InvalidInput {
+        /// Description of the problem
+        message: String,
+    },
+    /// Values of different types at the same locations, if the strict mode is enabled
+    TypeConflict {
+        /// Locations of the values with their types, sorted by location
+        conflicts: Vec<TypeConflict>,
+    },
+    /// A value of a type that is forbidden by `JSONSchema::forbidden_types`
+    ForbiddenType {
+        /// JSON Pointer to the value in the input document
+        pointer: String,
+        /// Name of the value type
+        type_name: String,
+    },
+    /// The input is a scalar, while `JSONSchema::require_composite_root` requires an object
+    /// or an array
+    ScalarRoot {
+        /// Name of the value type
+        type_name: String,
+    },
+}
+
+/// Values of different types at the same location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeConflict {
+    /// JSON Pointer to the values, array items are referred to by `*`
+    pub pointer: String,
+    /// Names of the conflicting types
+    pub types: Vec<String>,
+}
+
+impl fmt::Display for InferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InferError::NonFiniteNumber { pointer } => {
+                write!(f, "Non-finite number at '{}'", pointer)
+            }
+            InferError::InvalidInput { message } => write!(f, "Invalid input: {}", message),
+            InferError::TypeConflict { conflicts } => {
+                write!(f, "Conflicting types")?;
+                for (idx, conflict) in conflicts.iter().enumerate() {
+                    let separator = if idx == 0 { ":" } else { ";" };
+                    write!(f, "{} '{}' is ", separator, conflict.pointer)?;
+                    for (idx, type_) in conflict.types.iter().enumerate() {
+                        if idx > 0 {
+                            write!(f, " or ")?;
+                        }
+                        write!(f, "{}", type_)?;
+                    }
+                }
+                Ok(())
+            }
+            InferError::ForbiddenType { pointer, type_name } => {
+                write!(f, "Forbidden type {} at '{}'", type_name, pointer)
+            }
+            InferError::ScalarRoot { type_name } => {
+                write!(f, "Expected an object or an array, got {}", type_name)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InferError {}
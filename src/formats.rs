@@ -0,0 +1,764 @@
+//! String format detection.
+use serde::Deserialize;
+
+/// String formats that can be detected during inference.
+/// In configuration files, formats are referred to by their names, e.g. "date-time"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    /// Strings containing a 32-bit integer, e.g. "42"
+    Integer,
+    /// Full dates, e.g. "2020-01-01". Detected only with the `std` feature
+    Date,
+    /// RFC 3339 timestamps, e.g. "2018-11-13T20:20:39+00:00". Detected only with the `std` feature
+    DateTime,
+    /// JSON Pointers, e.g. "/a/b/0"
+    JsonPointer,
+    /// Relative JSON Pointers, e.g. "1/foo" or "0#". A bare number is a valid relative pointer
+    /// too, but it is not detected, as any integer would be reported then.
+    /// The format is defined since Draft 7
+    RelativeJsonPointer,
+    /// Regular expressions, e.g. "^[a-z]+$".
+    /// Only strings with at least one special character are considered, otherwise any plain text
+    /// would be reported as a regular expression. Detected only with the `std` feature
+    Regex,
+    /// ISO 8601 durations, e.g. "P3Y6M4DT12H30M5S". The format is defined since Draft 2019-09
+    Duration,
+    /// Hex colors with 3 or 6 digits, e.g. "#fff" or "#1a2b3c". The format name is not standard
+    Color,
+    /// IPv4 or IPv6 addresses with a prefix length, e.g. "10.0.0.0/8" or "2001:db8::/32".
+    /// The format is not standard and is named "x-cidr"
+    #[serde(rename = "x-cidr")]
+    Cidr,
+    /// MAC addresses of six hex octets separated by ":" or "-", e.g. "00:1A:2B:3C:4D:5E".
+    /// The format is not standard and is named "x-mac"
+    #[serde(rename = "x-mac")]
+    Mac,
+    /// Email addresses with an ASCII domain and local part, e.g. "user@example.com"
+    Email,
+    /// Email addresses with non-ASCII characters, e.g. "用户@例子.广告"
+    IdnEmail,
+    /// Host names with at least two labels, e.g. "example.com"
+    Hostname,
+    /// Host names with non-ASCII characters, e.g. "例子.广告"
+    IdnHostname,
+    /// Absolute URIs with a scheme of at least two characters, e.g. "https://example.com/a?b=c"
+    Uri,
+    /// Absolute URIs with non-ASCII characters, e.g. "https://example.com/café"
+    Iri,
+    /// Absolute URIs or relative references starting with "/", ".", "?" or "#", e.g. "../a".
+    /// Other relative references are not detected, otherwise any word would be reported
+    UriReference,
+    /// URI references with non-ASCII characters, e.g. "/café"
+    IriReference,
+}
+
+/// How strictly strings are checked to be email addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailStrictness {
+    /// A non-empty local part and domain separated by "@", without whitespace, e.g. "a@b"
+    Lenient,
+    /// A dot-separated local part and a host name with at least two labels, e.g. "a@b.com"
+    Strict,
+}
+
+impl Format {
+    /// Formats detected by default
+    pub const DEFAULT: &'static [Format] = &[Format::Integer, Format::Date, Format::DateTime];
+
+    /// All supported formats, from the most specific ones to the least specific ones
+    pub const ALL: &'static [Format] = &[
+        Format::Integer,
+        Format::Date,
+        Format::DateTime,
+        Format::Duration,
+        Format::Color,
+        Format::Cidr,
+        Format::Mac,
+        Format::JsonPointer,
+        Format::RelativeJsonPointer,
+        Format::Uri,
+        Format::Iri,
+        Format::UriReference,
+        Format::IriReference,
+        Format::Email,
+        Format::IdnEmail,
+        Format::Hostname,
+        Format::IdnHostname,
+        Format::Regex,
+    ];
+
+    /// Name of the format as used in the `format` keyword
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Integer => "integer",
+            Format::Date => "date",
+            Format::DateTime => "date-time",
+            Format::JsonPointer => "json-pointer",
+            Format::RelativeJsonPointer => "relative-json-pointer",
+            Format::Regex => "regex",
+            Format::Duration => "duration",
+            Format::Color => "color",
+            Format::Cidr => "x-cidr",
+            Format::Mac => "x-mac",
+            Format::Email => "email",
+            Format::IdnEmail => "idn-email",
+            Format::Hostname => "hostname",
+            Format::IdnHostname => "idn-hostname",
+            Format::Uri => "uri",
+            Format::Iri => "iri",
+            Format::UriReference => "uri-reference",
+            Format::IriReference => "iri-reference",
+        }
+    }
+
+    /// Find a supported format by its name, e.g. "date-time"
+    pub fn from_name(name: &str) -> Option<Format> {
+        Format::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+    }
+
+    /// Check whether the given string has this format
+    pub fn matches(&self, string: &str) -> bool {
+        self.matches_with(string, EmailStrictness::Strict)
+    }
+
+    /// Check whether the given string has this format, checking email addresses as configured
+    pub(crate) fn matches_with(&self, string: &str, email: EmailStrictness) -> bool {
+        match self {
+            Format::Integer => string.parse::<i32>().is_ok(),
+            Format::Date => is_date(string),
+            Format::DateTime => is_date_time(string),
+            Format::JsonPointer => is_json_pointer(string),
+            Format::RelativeJsonPointer => is_relative_json_pointer(string),
+            Format::Regex => is_regex(string),
+            Format::Duration => is_duration(string),
+            Format::Color => is_color(string),
+            Format::Cidr => is_cidr(string),
+            Format::Mac => is_mac(string),
+            Format::Email => string.is_ascii() && is_email(string, email),
+            Format::IdnEmail => !string.is_ascii() && is_email(string, email),
+            Format::Hostname => string.is_ascii() && is_hostname(string),
+            Format::IdnHostname => !string.is_ascii() && is_hostname(string),
+            Format::Uri => string.is_ascii() && is_uri(string),
+            Format::Iri => !string.is_ascii() && is_uri(string),
+            Format::UriReference => string.is_ascii() && is_uri_reference(string),
+            Format::IriReference => !string.is_ascii() && is_uri_reference(string),
+        }
+    }
+}
+
+/// Detect a format of the given string among all supported formats, without running inference.
+/// Formats are checked in the order of `Format::ALL`, so the most specific matching format
+/// is returned, e.g. "42" is an integer even though it is a valid regular expression too.
+///
+/// ```rust
+/// use infers_jsonschema::detect_format;
+///
+/// assert_eq!(detect_format("#1a2b3c"), Some("color"));
+/// assert_eq!(detect_format("plain text"), None);
+/// ```
+pub fn detect_format(string: &str) -> Option<&'static str> {
+    infer_format(string, Format::ALL, EmailStrictness::Strict)
+}
+
+/// Infer a format of the given string.
+///
+/// Formats are checked in the given order and the first matching one is returned.
+pub(crate) fn infer_format(
+    string: &str,
+    formats: &[Format],
+    email: EmailStrictness,
+) -> Option<&'static str> {
+    formats
+        .iter()
+        .find(|format| format.matches_with(string, email))
+        .map(Format::name)
+}
+
+/// A non-empty JSON Pointer where `~` is only used as an escape sequence: `~0` or `~1`.
+/// The empty string is a valid pointer too, but it is too ambiguous to report
+fn is_json_pointer(string: &str) -> bool {
+    if !string.starts_with('/') {
+        return false;
+    }
+    let mut chars = string.chars();
+    while let Some(char) = chars.next() {
+        if char == '~' && !matches!(chars.next(), Some('0') | Some('1')) {
+            return false;
+        }
+    }
+    true
+}
+
+/// A non-negative integer without leading zeros followed by "#" or a JSON Pointer
+fn is_relative_json_pointer(string: &str) -> bool {
+    let rest = string.trim_start_matches(|char: char| char.is_ascii_digit());
+    let digits = &string[..string.len() - rest.len()];
+    !digits.is_empty()
+        && (digits == "0" || !digits.starts_with('0'))
+        && (rest == "#" || is_json_pointer(rest))
+}
+
+#[cfg(feature = "std")]
+fn is_date(string: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(string, "%Y-%m-%d").is_ok()
+}
+
+#[cfg(feature = "std")]
+fn is_date_time(string: &str) -> bool {
+    chrono::DateTime::parse_from_rfc3339(string).is_ok()
+}
+
+#[cfg(feature = "std")]
+fn is_regex(string: &str) -> bool {
+    string.contains(|char| "^$.*+?()[]{}|\\".contains(char)) && regex::Regex::new(string).is_ok()
+}
+
+// Parsers for these formats need `std`
+
+#[cfg(not(feature = "std"))]
+fn is_date(_: &str) -> bool {
+    false
+}
+
+#[cfg(not(feature = "std"))]
+fn is_date_time(_: &str) -> bool {
+    false
+}
+
+#[cfg(not(feature = "std"))]
+fn is_regex(_: &str) -> bool {
+    false
+}
+
+/// A duration as defined in RFC 3339, Appendix A, i.e. "P" followed by either a number of weeks or
+/// date components followed by time components after "T". Components have to be in order,
+/// but any of them may be omitted as long as there is at least one
+fn is_duration(string: &str) -> bool {
+    let rest = match string.strip_prefix('P') {
+        Some(rest) => rest,
+        None => return false,
+    };
+    if let Some(weeks) = rest.strip_suffix('W') {
+        return !weeks.is_empty() && weeks.bytes().all(|byte| byte.is_ascii_digit());
+    }
+    let (date, time) = match rest.find('T') {
+        Some(idx) => (&rest[..idx], Some(&rest[idx + 1..])),
+        None => (rest, None),
+    };
+    match (count_components(date, b"YMD"), time) {
+        (Some(date), None) => date > 0,
+        (Some(_), Some(time)) => count_components(time, b"HMS").is_some_and(|time| time > 0),
+        (None, _) => false,
+    }
+}
+
+/// Number of components like "12H", if the designators are in the given order
+fn count_components(part: &str, designators: &[u8]) -> Option<usize> {
+    let mut count = 0;
+    let mut digits = 0;
+    // Designators that are still allowed
+    let mut allowed = designators;
+    for byte in part.bytes() {
+        if byte.is_ascii_digit() {
+            digits += 1;
+        } else {
+            let position = allowed.iter().position(|&designator| designator == byte)?;
+            if digits == 0 {
+                return None;
+            }
+            allowed = &allowed[position + 1..];
+            digits = 0;
+            count += 1;
+        }
+    }
+    if digits == 0 {
+        Some(count)
+    } else {
+        None
+    }
+}
+
+fn is_color(string: &str) -> bool {
+    match string.strip_prefix('#') {
+        Some(digits) => {
+            matches!(digits.len(), 3 | 6) && digits.bytes().all(|byte| byte.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+/// An IP address followed by "/" and a prefix length without leading zeros that fits the address.
+/// Host bits may be set, as in "192.168.1.1/24", which is common in interface configurations
+fn is_cidr(string: &str) -> bool {
+    let (address, prefix) = match string.split_once('/') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let max_prefix = if address.parse::<core::net::Ipv4Addr>().is_ok() {
+        32
+    } else if address.parse::<core::net::Ipv6Addr>().is_ok() {
+        128
+    } else {
+        return false;
+    };
+    !prefix.is_empty()
+        && prefix.bytes().all(|byte| byte.is_ascii_digit())
+        && (prefix == "0" || !prefix.starts_with('0'))
+        && prefix
+            .parse::<u8>()
+            .is_ok_and(|prefix| prefix <= max_prefix)
+}
+
+/// Six pairs of hex digits separated by the same separator, either ":" or "-"
+fn is_mac(string: &str) -> bool {
+    let separator = match string.as_bytes().get(2) {
+        Some(b':') => ':',
+        Some(b'-') => '-',
+        _ => return false,
+    };
+    let mut octets = 0;
+    for octet in string.split(separator) {
+        if octet.len() != 2 || !octet.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return false;
+        }
+        octets += 1;
+    }
+    octets == 6
+}
+
+/// A dot-separated local part followed by "@" and a host name. Quoted local parts and IP domains
+/// are not detected
+fn is_email(string: &str, strictness: EmailStrictness) -> bool {
+    match string.rsplit_once('@') {
+        Some((local, domain)) if strictness == EmailStrictness::Lenient => {
+            !local.is_empty() && !domain.is_empty() && !string.contains(char::is_whitespace)
+        }
+        Some((local, domain)) => {
+            local.len() <= 64
+                && local.split('.').all(|atom| {
+                    !atom.is_empty()
+                        && atom.chars().all(|char| {
+                            char.is_alphanumeric()
+                                || !char.is_ascii()
+                                || "!#$%&'*+/=?^_`{|}~-".contains(char)
+                        })
+                })
+                && is_hostname(domain)
+        }
+        None => false,
+    }
+}
+
+/// At least two labels of alphanumeric characters and hyphens that don't start or end with
+/// a hyphen. Single labels are valid host names too, but any word would be reported then.
+/// Top-level domains are never numeric, so decimals like "3.14" and IPv4 addresses are not
+/// host names
+fn is_hostname(string: &str) -> bool {
+    string.len() <= 253
+        && string.contains('.')
+        && !string
+            .rsplit('.')
+            .next()
+            .is_some_and(|label| label.bytes().all(|byte| byte.is_ascii_digit()))
+        && string.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|char| char.is_alphanumeric() || char == '-')
+        })
+}
+
+/// A scheme followed by ":" and a non-empty rest. Single-letter schemes are not detected, as they
+/// are more likely Windows drive letters
+fn is_uri(string: &str) -> bool {
+    match string.split_once(':') {
+        Some((scheme, rest)) => {
+            scheme.len() >= 2
+                && scheme.starts_with(|char: char| char.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|char| char.is_ascii_alphanumeric() || "+-.".contains(char))
+                && !rest.is_empty()
+                && has_uri_characters(rest)
+        }
+        None => false,
+    }
+}
+
+fn is_uri_reference(string: &str) -> bool {
+    is_uri(string) || (string.starts_with(['/', '.', '?', '#']) && has_uri_characters(string))
+}
+
+/// Characters allowed in URIs, percent-encoded octets and non-ASCII characters allowed in IRIs
+fn has_uri_characters(string: &str) -> bool {
+    let mut chars = string.chars();
+    while let Some(char) = chars.next() {
+        let valid = match char {
+            '%' => {
+                chars.next().is_some_and(|char| char.is_ascii_hexdigit())
+                    && chars.next().is_some_and(|char| char.is_ascii_hexdigit())
+            }
+            _ if char.is_ascii() => {
+                char.is_ascii_alphanumeric() || "-._~:/?#[]@!$&'()*+,;=".contains(char)
+            }
+            // Noncharacters are not allowed in IRIs
+            _ => {
+                !char.is_control()
+                    && !char.is_whitespace()
+                    && u32::from(char) & 0xfffe != 0xfffe
+                    && !('\u{fdd0}'..='\u{fdef}').contains(&char)
+            }
+        };
+        if !valid {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_pointer() {
+        for pointer in &["/a/b/0", "/", "/a~0b/c~1d", "/foo bar"] {
+            assert!(Format::JsonPointer.matches(pointer), "{}", pointer);
+        }
+        for string in &["a/b", "", "/a~2", "/a~", "plain"] {
+            assert!(!Format::JsonPointer.matches(string), "{}", string);
+        }
+    }
+
+    #[test]
+    fn test_relative_json_pointer() {
+        for pointer in &["0#", "1/foo", "0/", "12/a~1b/0"] {
+            assert!(Format::RelativeJsonPointer.matches(pointer), "{}", pointer);
+        }
+        for string in &[
+            "0", "12", "#", "/a", "01/a", "1#/a", "-1/a", "1/a~2", "plain",
+        ] {
+            assert!(!Format::RelativeJsonPointer.matches(string), "{}", string);
+        }
+        // Not detected by default
+        assert_eq!(
+            infer_format("1/foo", Format::DEFAULT, EmailStrictness::Strict),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_regex() {
+        for regex in &["^[a-z]+$", "\\d{3}-\\d{4}", "foo|bar"] {
+            assert!(Format::Regex.matches(regex), "{}", regex);
+        }
+        for string in &["plain", "[unclosed", "(a"] {
+            assert!(!Format::Regex.matches(string), "{}", string);
+        }
+    }
+
+    #[test]
+    fn test_duration() {
+        for duration in &["P3Y6M4DT12H30M5S", "P1D", "PT5M", "P2W", "P1Y2D", "PT1H5S"] {
+            assert!(Format::Duration.matches(duration), "{}", duration);
+        }
+        for string in &[
+            "Paris", "P", "PT", "P1", "P1DT", "P1M1Y", "PT1S1H", "P1WT1H", "1D", "PTx",
+        ] {
+            assert!(!Format::Duration.matches(string), "{}", string);
+        }
+    }
+
+    #[test]
+    fn test_color() {
+        for color in &["#fff", "#1a2b3c", "#ABCDEF"] {
+            assert!(Format::Color.matches(color), "{}", color);
+        }
+        for string in &["#xyz", "fff", "#ffff", "#1a2b3g", "#", "# fff", "#1a2b3c4d"] {
+            assert!(!Format::Color.matches(string), "{}", string);
+        }
+        // Colors are not regular expressions or JSON pointers
+        assert_eq!(
+            infer_format(
+                "#fff",
+                &[Format::Regex, Format::JsonPointer, Format::Color],
+                EmailStrictness::Strict
+            ),
+            Some("color")
+        );
+    }
+
+    #[test]
+    fn test_cidr() {
+        for cidr in &[
+            "10.0.0.0/8",
+            "192.168.1.1/24",
+            "0.0.0.0/0",
+            "255.255.255.255/32",
+            "2001:db8::/32",
+            "::1/128",
+        ] {
+            assert!(Format::Cidr.matches(cidr), "{}", cidr);
+        }
+        for string in &[
+            "10.0.0.0",
+            "10.0.0.0/",
+            "10.0.0.0/33",
+            "10.0.0.0/08",
+            "10.0.0.0/+8",
+            "10.0.0/8",
+            "256.0.0.0/8",
+            "010.0.0.0/8",
+            "2001:db8::/129",
+            "example.com/8",
+            "10.0.0.0/8/8",
+        ] {
+            assert!(!Format::Cidr.matches(string), "{}", string);
+        }
+    }
+
+    #[test]
+    fn test_mac() {
+        for mac in &[
+            "00:1A:2B:3C:4D:5E",
+            "00-1a-2b-3c-4d-5e",
+            "ff:ff:ff:ff:ff:ff",
+        ] {
+            assert!(Format::Mac.matches(mac), "{}", mac);
+        }
+        for string in &[
+            "00:1A:2B:3C:4D",
+            "00:1A:2B:3C:4D:5E:6F",
+            "00:1A-2B:3C:4D:5E",
+            "00:1A:2B:3C:4D:5G",
+            "0:1A:2B:3C:4D:5E",
+            "001A.2B3C.4D5E",
+            "00:1A:2B:3C:4D:5E:",
+        ] {
+            assert!(!Format::Mac.matches(string), "{}", string);
+        }
+        // Not detected by default
+        assert_eq!(
+            infer_format(
+                "00:1A:2B:3C:4D:5E",
+                Format::DEFAULT,
+                EmailStrictness::Strict
+            ),
+            None
+        );
+        assert_eq!(detect_format("10.0.0.0/8"), Some("x-cidr"));
+        assert_eq!(Format::from_name("x-mac"), Some(Format::Mac));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_infer_format_order() {
+        assert_eq!(
+            infer_format("42", Format::DEFAULT, EmailStrictness::Strict),
+            Some("integer")
+        );
+        assert_eq!(
+            infer_format("42", &[Format::Date], EmailStrictness::Strict),
+            None
+        );
+        assert_eq!(
+            infer_format(
+                "/a/b",
+                &[Format::Regex, Format::JsonPointer],
+                EmailStrictness::Strict
+            ),
+            Some("json-pointer")
+        );
+        assert_eq!(
+            infer_format(
+                "/a.b",
+                &[Format::Regex, Format::JsonPointer],
+                EmailStrictness::Strict
+            ),
+            Some("regex")
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "std"))]
+    fn test_without_std() {
+        assert_eq!(
+            infer_format("42", Format::DEFAULT, EmailStrictness::Strict),
+            Some("integer")
+        );
+        assert_eq!(
+            infer_format("2020-01-01", Format::DEFAULT, EmailStrictness::Strict),
+            None
+        );
+        assert!(!Format::Regex.matches("^[a-z]+$"));
+    }
+
+    #[test]
+    fn test_email() {
+        assert!(Format::Email.matches("user.name+tag@example.com"));
+        assert!(!Format::IdnEmail.matches("user.name+tag@example.com"));
+        for email in &["用户@例子.广告", "josé@example.com", "user@bücher.de"] {
+            assert!(Format::IdnEmail.matches(email), "{}", email);
+            assert!(!Format::Email.matches(email), "{}", email);
+        }
+        for string in &[
+            "plain",
+            "@example.com",
+            "user@",
+            "a..b@example.com",
+            "user@localhost",
+        ] {
+            assert!(!Format::Email.matches(string), "{}", string);
+        }
+    }
+
+    #[test]
+    fn test_email_strictness() {
+        for email in &["a@b", "user@localhost", "用户@例子"] {
+            let format = if email.is_ascii() {
+                Format::Email
+            } else {
+                Format::IdnEmail
+            };
+            assert!(
+                format.matches_with(email, EmailStrictness::Lenient),
+                "{}",
+                email
+            );
+            assert!(
+                !format.matches_with(email, EmailStrictness::Strict),
+                "{}",
+                email
+            );
+        }
+        for string in &["plain", "@b", "a@", "a b@c"] {
+            assert!(
+                !Format::Email.matches_with(string, EmailStrictness::Lenient),
+                "{}",
+                string
+            );
+        }
+    }
+
+    #[test]
+    fn test_hostname() {
+        assert!(Format::Hostname.matches("www.example-site.com"));
+        assert!(!Format::IdnHostname.matches("www.example-site.com"));
+        assert!(Format::IdnHostname.matches("bücher.de"));
+        assert!(!Format::Hostname.matches("bücher.de"));
+        for string in &[
+            "localhost",
+            "-a.com",
+            "a-.com",
+            "a..com",
+            "a b.com",
+            "user@example.com",
+            "3.14",
+            "192.168.0.1",
+            "example.123",
+        ] {
+            assert!(!Format::Hostname.matches(string), "{}", string);
+        }
+        // Only the last label can't be numeric
+        assert!(Format::Hostname.matches("1.2.example.com"));
+        assert!(!Format::IdnHostname.matches("例子.123"));
+        assert!(!Format::Email.matches("user@192.168.0.1"));
+    }
+
+    #[test]
+    fn test_from_name() {
+        for format in Format::ALL {
+            assert_eq!(Format::from_name(format.name()), Some(*format));
+        }
+        assert_eq!(Format::from_name("unknown"), None);
+    }
+
+    #[test]
+    fn test_detect_format() {
+        for (string, expected) in &[
+            ("42", "integer"),
+            ("P1D", "duration"),
+            ("#fff", "color"),
+            ("/a/b", "json-pointer"),
+            ("1/a", "relative-json-pointer"),
+            ("user@example.com", "email"),
+            ("user@bücher.de", "idn-email"),
+            ("example.com", "hostname"),
+            ("bücher.de", "idn-hostname"),
+            ("https://example.com", "uri"),
+            ("?a=b", "uri-reference"),
+        ] {
+            assert_eq!(detect_format(string), Some(*expected), "{}", string);
+        }
+        assert_eq!(detect_format("plain"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_detect_format_with_std() {
+        assert_eq!(detect_format("2020-01-01"), Some("date"));
+        assert_eq!(
+            detect_format("2018-11-13T20:20:39+00:00"),
+            Some("date-time")
+        );
+        assert_eq!(detect_format("^[a-z]+$"), Some("regex"));
+    }
+
+    #[test]
+    fn test_unusual_characters() {
+        for string in &[
+            "\u{0}\u{1}\u{7f}",
+            "P\u{0}D",
+            "#\u{10ffff}\u{10ffff}",
+            "\u{1f600}~",
+            "\u{1f600}@\u{1f600}",
+            "\u{feff}2020-01-01",
+            "\u{fffd}\u{fffd}\u{202e}",
+            "42\u{0}",
+        ] {
+            assert_eq!(detect_format(string), None, "{:?}", string);
+        }
+    }
+
+    #[test]
+    fn test_uri() {
+        for uri in &[
+            "https://example.com/a?b=c#d",
+            "urn:isbn:0451450523",
+            "mailto:a@b.c",
+        ] {
+            assert!(Format::Uri.matches(uri), "{}", uri);
+            assert!(!Format::Iri.matches(uri), "{}", uri);
+        }
+        for iri in &["https://example.com/café", "http://例子.广告/"] {
+            assert!(Format::Iri.matches(iri), "{}", iri);
+            assert!(!Format::Uri.matches(iri), "{}", iri);
+        }
+        for string in &[
+            "C:/dir",
+            "http://a b",
+            "1ab:c",
+            "ab:",
+            "http://%zz",
+            "plain",
+        ] {
+            assert!(!Format::Uri.matches(string), "{}", string);
+        }
+        assert!(Format::UriReference.matches("../a?b#c"));
+        assert!(Format::UriReference.matches("https://example.com"));
+        assert!(!Format::UriReference.matches("a/b"));
+        assert!(Format::IriReference.matches("/café"));
+        assert_eq!(detect_format("https://example.com/café"), Some("iri"));
+        assert_eq!(detect_format("https://example.com/"), Some("uri"));
+        assert_eq!(detect_format("./é"), Some("iri-reference"));
+    }
+}
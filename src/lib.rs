@@ -1,10 +1,67 @@
-use chrono::{DateTime, NaiveDate};
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+extern crate alloc;
+
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use hash::{fnv1a, FnvHasher};
+use random::{sample_indices, Rng};
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
 use rayon::prelude::*;
+use recorder::Recorder;
+use serde::Deserialize;
 use serde_json::{json, Map, Number, Value};
-use std::collections::hash_map::DefaultHasher;
-use std::collections::{BTreeMap, HashSet};
-use std::hash::{Hash, Hasher};
-use std::iter::FromIterator;
+
+mod avro;
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+mod base64;
+mod builder;
+#[cfg(feature = "cbor")]
+mod cbor;
+mod config;
+mod error;
+mod formats;
+mod hash;
+mod merge;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+mod openapi;
+mod random;
+mod recorder;
+mod refine;
+mod stats;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "toml")]
+mod toml;
+mod typescript;
+mod walk;
+pub use avro::to_avro;
+pub use builder::SchemaBuilder;
+#[cfg(feature = "cbor")]
+pub use cbor::infer_cbor;
+pub use config::Config;
+pub use error::{InferError, TypeConflict};
+use formats::infer_format;
+pub use formats::{detect_format, EmailStrictness, Format};
+pub use merge::merge;
+#[cfg(feature = "msgpack")]
+pub use msgpack::infer_msgpack;
+use stats::Collector;
+pub use stats::{InferStats, MergeStats, PathStats};
+#[cfg(feature = "std")]
+pub use stream::infer_reader;
+#[cfg(feature = "toml")]
+pub use toml::infer_toml_str;
+pub use typescript::to_typescript;
 
 #[derive(PartialEq)]
 pub struct ValueWrapper<'a>(&'a Value);
@@ -32,420 +89,6269 @@ impl<'a> Hash for ValueWrapper<'a> {
                 }
             }
             Value::Object(ref map) => {
-                let mut hash = 0;
+                let mut hashes = Vec::with_capacity(map.len());
                 for (k, v) in map {
                     // We have no way of building a new hasher of type `H`, so we
-                    // hardcode using the default hasher of a hash map.
-                    let mut item_hasher = DefaultHasher::new();
+                    // hardcode using FNV-1a.
+                    let mut item_hasher = FnvHasher::default();
                     k.hash(&mut item_hasher);
                     ValueWrapper(v).hash(&mut item_hasher);
-                    hash ^= item_hasher.finish();
+                    hashes.push(item_hasher.finish());
+                }
+                // Sorting makes the result independent of the key order. Unlike XOR-ing the
+                // hashes together, it doesn't let different entries cancel each other out
+                hashes.sort_unstable();
+                state.write_usize(hashes.len());
+                for hash in hashes {
+                    state.write_u64(hash);
                 }
-                state.write_u64(hash);
             }
         }
     }
 }
 
-pub struct JSONSchema<'a> {
-    input: &'a Value,
-    detect_format: bool,
+/// Hash of a value, e.g. of a schema, that is the same for equal values regardless of the order
+/// of object keys. It is FNV-1a based, so it is the same on all platforms. Different values may
+/// have the same hash, so values with equal hashes should still be compared to be sure.
+///
+/// ```rust
+/// use infers_jsonschema::canonical_hash;
+/// use serde_json::json;
+///
+/// assert_eq!(
+///     canonical_hash(&json!({"type": "integer", "minimum": 0})),
+///     canonical_hash(&json!({"minimum": 0, "type": "integer"}))
+/// );
+/// ```
+pub fn canonical_hash(value: &Value) -> u64 {
+    let mut hasher = FnvHasher::default();
+    ValueWrapper(value).hash(&mut hasher);
+    hasher.finish()
 }
 
-impl JSONSchema<'_> {
-    pub fn new(input: &Value) -> JSONSchema {
-        JSONSchema {
-            input,
-            detect_format: true,
-        }
+/// Add a schema to schemas keyed by their hashes, unless it is already there. Hashes may
+/// collide, therefore schemas with the same hash are compared too
+pub(crate) fn insert_distinct(schemas: &mut BTreeMap<u64, Vec<Value>>, hash: u64, schema: Value) {
+    let known = schemas.entry(hash).or_default();
+    if !known.contains(&schema) {
+        known.push(schema);
     }
+}
 
-    pub fn detect_format(mut self, detect_format: bool) -> Self {
-        self.detect_format = detect_format;
-        self
+/// Information about the input value a sub-schema was inferred from
+pub struct SchemaContext<'a> {
+    pointer: String,
+    value: &'a Value,
+}
+
+impl SchemaContext<'_> {
+    /// JSON Pointer to the value in the input document
+    pub fn pointer(&self) -> &str {
+        &self.pointer
     }
 
-    pub fn infer(&self) -> Value {
-        let mut result = self._infer(self.input);
-        result.as_object_mut().unwrap().insert(
-            "$schema".into(),
-            Value::String("http://json-schema.org/draft-07/schema#".into()),
-        );
-        result
+    /// The value the sub-schema was inferred from
+    pub fn value(&self) -> &Value {
+        self.value
     }
+}
 
-    fn _infer(&self, data: &Value) -> Value {
-        match data {
-            Value::Null => json!({"type": "null"}),
-            Value::Bool(_) => json!({"type": "boolean"}),
-            Value::String(string) => self.infer_string(string, self.detect_format),
-            Value::Number(number) => self.infer_number(number),
-            Value::Array(array) => self.infer_array(array),
-            Value::Object(object) => self.infer_object(object),
+/// Location of a value in the input document
+#[derive(Clone, Copy)]
+enum Location<'a> {
+    Root,
+    Key(&'a Location<'a>, &'a str),
+    Index(&'a Location<'a>, usize),
+    /// Any item of an array or value of a map, e.g. of merged schemas of all items
+    Items(&'a Location<'a>),
+}
+
+impl Location<'_> {
+    /// Build a JSON Pointer to this location
+    fn pointer(&self) -> String {
+        match self {
+            Location::Root => String::new(),
+            Location::Key(parent, key) => {
+                format!(
+                    "{}/{}",
+                    parent.pointer(),
+                    key.replace('~', "~0").replace('/', "~1")
+                )
+            }
+            Location::Index(parent, idx) => format!("{}/{}", parent.pointer(), idx),
+            Location::Items(parent) => format!("{}/*", parent.pointer()),
         }
     }
 
-    fn infer_string(&self, string: &str, detect_format: bool) -> Value {
-        let mut data = json!({"type": "string"});
-        if detect_format {
-            if let Some(format_name) = infer_format(&string) {
-                data["format"] = Value::String(format_name.into());
-            }
+    /// Build a JSON Pointer to this location, where array indices are `*`, so all items of
+    /// an array share it
+    fn path(&self) -> String {
+        match self {
+            Location::Root => String::new(),
+            Location::Key(parent, key) => format!("{}/{}", parent.path(), escape_token(key)),
+            Location::Index(parent, _) | Location::Items(parent) => format!("{}/*", parent.path()),
         }
-        data
     }
 
-    fn infer_number(&self, number: &Number) -> Value {
-        if number.is_f64() {
-            json!({"type": "number"})
-        } else {
-            json!({"type": "integer"})
+    /// The number of objects containing this location, array items don't add to it
+    fn depth(&self) -> usize {
+        match self {
+            Location::Root => 0,
+            Location::Key(parent, _) => parent.depth() + 1,
+            Location::Index(parent, _) | Location::Items(parent) => parent.depth(),
+        }
+    }
+}
+
+/// Escape a reference token of a JSON Pointer
+fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Add `$schema` to the root schema. Non-object schemas, e.g. `true` set by a hook,
+/// can't have keywords, therefore they are wrapped in `allOf`
+pub(crate) fn set_schema_uri(schema: &mut Value, uri: String) {
+    if !schema.is_object() {
+        *schema = json!({ "allOf": [schema.take()] });
+    }
+    schema["$schema"] = Value::String(uri);
+}
+
+/// Add `x-optional: true` to properties that are not in `required` and remove `required`
+/// unless it should be kept
+fn annotate_optional(schema: &mut Value, keep_required: bool) {
+    let map = match schema.as_object_mut() {
+        Some(map) if map.contains_key("properties") => map,
+        _ => return,
+    };
+    let required = if keep_required {
+        map.get("required").cloned()
+    } else {
+        map.remove("required")
+    };
+    let required = required.and_then(into_array).unwrap_or_default();
+    if let Some(Value::Object(properties)) = map.get_mut("properties") {
+        for (name, property) in properties.iter_mut() {
+            if !required.iter().any(|item| item == name.as_str()) {
+                if let Value::Object(property) = property {
+                    property.insert("x-optional".into(), Value::Bool(true));
+                }
+            }
         }
     }
+}
 
-    /// Infer schema for an array
-    fn infer_array(&self, array: &[Value]) -> Value {
-        let mut data = json!({"type": "array"});
-        let items: BTreeMap<u64, Value> = if array.len() > 8 {
-            array
-                .par_iter()
-                .map(|item| {
-                    let inferred = self._infer(item);
-                    let wrapper = ValueWrapper(&inferred);
-                    let mut hasher = DefaultHasher::new();
-                    wrapper.hash(&mut hasher);
-                    (hasher.finish(), inferred)
-                })
-                .collect()
-        } else {
-            array
+/// Remove keywords that accept any value
+fn remove_redundant_keywords(schema: &mut Value) {
+    if let Value::Object(map) = schema {
+        map.retain(|keyword, value| match keyword.as_str() {
+            "required" => !matches!(value, Value::Array(items) if items.is_empty()),
+            "properties" => !matches!(value, Value::Object(properties) if properties.is_empty()),
+            "items" | "additionalProperties" => !is_empty_schema(value) && *value != true,
+            _ => true,
+        });
+    }
+}
+
+/// Replace `anyOf` of schemas that only have distinct types with an array of the types
+/// and sort arrays of types
+fn collapse_type_union(schema: &mut Value, order: TypeArrayOrder) {
+    let map = match schema.as_object_mut() {
+        Some(map) => map,
+        None => return,
+    };
+    let types = match map.get("anyOf") {
+        Some(Value::Array(branches)) if !map.contains_key("type") => {
+            let types: Option<Vec<Value>> = branches
                 .iter()
-                .map(|item| {
-                    let inferred = self._infer(item);
-                    let wrapper = ValueWrapper(&inferred);
-                    let mut hasher = DefaultHasher::new();
-                    wrapper.hash(&mut hasher);
-                    (hasher.finish(), inferred)
+                .map(|branch| match branch.as_object() {
+                    Some(branch) if branch.len() == 1 => branch
+                        .get("type")
+                        .filter(|type_| type_.is_string())
+                        .cloned(),
+                    _ => None,
                 })
-                .collect()
+                .collect();
+            types.filter(|types| types.len() > 1 && !has_duplicates(types))
+        }
+        _ => None,
+    };
+    if let Some(types) = types {
+        map.remove("anyOf");
+        map.insert("type".into(), Value::Array(types));
+    }
+    if let Some(Value::Array(types)) = map.get_mut("type") {
+        order.sort(types);
+    }
+}
+
+fn has_duplicates(values: &[Value]) -> bool {
+    values
+        .iter()
+        .enumerate()
+        .any(|(idx, value)| values[..idx].contains(value))
+}
+
+/// Replace `anyOf` or `oneOf` with a single branch by the branch. Keywords next to the union,
+/// e.g. `$comment`, are kept, unless the branch has the same keywords
+fn flatten_single_branch(schema: &mut Value) {
+    let map = match schema.as_object_mut() {
+        Some(map) => map,
+        None => return,
+    };
+    for keyword in &["anyOf", "oneOf"] {
+        let branch = match map.get(*keyword) {
+            Some(Value::Array(branches)) if branches.len() == 1 => &branches[0],
+            _ => continue,
         };
-        let mut items = items.values().collect::<Vec<&Value>>();
-        if items.len() == 1 {
-            data["items"] = items.swap_remove(0).clone();
-        } else if let Some(merged) = try_merge(&items) {
-            data["items"] = merged
-        } else {
-            data["items"] = json!({ "anyOf": items });
+        match branch {
+            Value::Object(branch) if !branch.keys().any(|key| map.contains_key(key)) => {
+                let branch = branch.clone();
+                map.remove(*keyword);
+                map.extend(branch);
+            }
+            // A boolean schema can't be merged with other keywords
+            Value::Bool(_) if map.len() == 1 => {
+                *schema = branch.clone();
+                return;
+            }
+            _ => {}
         }
-        data
     }
+}
 
-    /// Infer schema for JSON object
-    fn infer_object(&self, object: &Map<String, Value>) -> Value {
-        let mut properties = BTreeMap::new();
-        let mut required = Vec::with_capacity(object.len());
-        for (key, value) in object.iter() {
-            required.push(key);
-            properties.insert(key, self._infer(&value));
+/// Whether the array is non-empty and all its items are integers from 0 to 255
+fn is_byte_array(array: &[Value]) -> bool {
+    !array.is_empty()
+        && array
+            .iter()
+            .all(|item| item.as_u64().is_some_and(|byte| byte <= 255))
+}
+
+/// Whether all the array items are different
+fn has_unique_items(array: &[Value]) -> bool {
+    let mut seen: BTreeMap<u64, Vec<&Value>> = BTreeMap::new();
+    array.iter().all(|item| {
+        let known = seen.entry(canonical_hash(item)).or_default();
+        if known.contains(&item) {
+            false
+        } else {
+            known.push(item);
+            true
         }
-        json!({"type": "object", "required": required, "properties": properties})
+    })
+}
+
+/// Remove a pair of bounds from the schema, if the upper one is present
+fn take_bounds(schema: &mut Value, min_keyword: &str, max_keyword: &str) -> Option<(u64, u64)> {
+    let map = schema.as_object_mut()?;
+    if !map.contains_key(max_keyword) {
+        return None;
     }
+    map.remove(min_keyword)
+        .as_ref()
+        .and_then(Value::as_u64)
+        .zip(map.remove(max_keyword).as_ref().and_then(Value::as_u64))
 }
 
-/// Shortcut for inference with default settings
-pub fn infer(input: &Value) -> Value {
-    JSONSchema::new(input).infer()
+/// Largest integer not greater than the value. `f64::floor` needs `std`
+fn floor(value: f64) -> f64 {
+    // Larger values have no fractional part
+    const EXACT: f64 = 4_503_599_627_370_496.0;
+    if value.is_nan() || value >= EXACT || value <= -EXACT {
+        return value;
+    }
+    let truncated = value as i64 as f64;
+    if truncated > value {
+        truncated - 1.0
+    } else {
+        truncated
+    }
 }
 
-/// Try to merge multiple object schemas into one
-fn try_merge(data: &[&Value]) -> Option<Value> {
-    if data
-        .iter()
-        .all(|item| item.get("type").unwrap() == "object")
+/// Remove numeric `minimum` and `maximum` from the schema, if it has both of them
+fn take_range(schema: &mut Value) -> Option<(Value, Value)> {
+    let map = schema.as_object_mut()?;
+    if !map.get("minimum").is_some_and(Value::is_number)
+        || !map.get("maximum").is_some_and(Value::is_number)
     {
-        let mut properties_types: BTreeMap<String, Vec<&Value>> = BTreeMap::new();
-        let mut known_required: Vec<HashSet<&str>> = vec![];
-        let mut new = json!({"type": "object"});
-        for item in data.iter() {
-            let properties = item.get("properties").unwrap().as_object().unwrap();
-            for (name, schema) in properties {
-                let known_types = properties_types
-                    .entry(name.clone())
-                    .or_insert_with(Vec::new);
-                if !known_types.contains(&schema) {
-                    known_types.push(schema)
-                }
-            }
-            collect_required(&mut known_required, item);
+        return None;
+    }
+    map.remove("minimum").zip(map.remove("maximum"))
+}
+
+/// Widen bounds to include both ranges, unbounded if any of them is
+fn join_bounds(left: Option<(u64, u64)>, right: Option<(u64, u64)>) -> Option<(u64, u64)> {
+    match (left, right) {
+        (Some((min, max)), Some((other_min, other_max))) => {
+            Some((min.min(other_min), max.max(other_max)))
         }
-        let map = new.as_object_mut().unwrap();
-        fill_required(map, known_required);
-        fill_properties(map, &properties_types);
-        return Some(new);
+        _ => None,
     }
-    None
 }
 
-fn collect_required<'a>(known_required: &mut Vec<HashSet<&'a str>>, item: &'a Value) {
-    let required = HashSet::from_iter(
-        item.get("required")
-            .unwrap()
-            .as_array()
-            .unwrap()
-            .iter()
-            .map(|x| x.as_str().unwrap()),
-    );
-    known_required.push(required);
+/// Types of the schemas, in their order
+fn schema_types(schemas: &[Value]) -> Vec<&'static str> {
+    const TYPES: &[&str] = &[
+        "null", "boolean", "integer", "number", "string", "array", "object",
+    ];
+    schemas
+        .iter()
+        .map(|schema| {
+            TYPES
+                .iter()
+                .find(|&&type_| schema["type"] == type_)
+                .copied()
+                .unwrap_or_default()
+        })
+        .collect()
 }
 
-/// Fill required properties
-/// There will be only properties that are common to all objects
-fn fill_required(map: &mut Map<String, Value>, known_required: Vec<HashSet<&str>>) {
-    if let Some(first_set) = known_required.first() {
-        let common_required = first_set
+/// Sort `anyOf` branches by their types. Branches of the same type keep their order
+fn order_branches(branches: &mut [Value], observed: &[&str], order: UnionOrder) {
+    // Integers are numbers too, e.g. after `unify_int_float`
+    let is_observed = |type_: &str, observed: &str| {
+        observed == type_ || (type_ == "number" && observed == "integer")
+    };
+    let first_seen = |type_: &str| {
+        observed
             .iter()
-            .filter(|&k| known_required.iter().all(|s| s.contains(k)))
-            .map(|x| json!(x))
-            .collect::<Vec<Value>>();
-        if !common_required.is_empty() {
-            map.insert("required".into(), Value::Array(common_required));
+            .position(|observed| is_observed(type_, observed))
+            .unwrap_or(usize::MAX)
+    };
+    match order {
+        UnionOrder::TypeName => {
+            branches.sort_by(
+                |left, right| match (left["type"].as_str(), right["type"].as_str()) {
+                    (Some(left), Some(right)) => left.cmp(right),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+            )
+        }
+        // Equally common types are ordered by their first appearance
+        UnionOrder::Frequency => branches.sort_by_key(|branch| {
+            let type_ = branch["type"].as_str().unwrap_or_default();
+            let count = observed
+                .iter()
+                .filter(|observed| is_observed(type_, observed))
+                .count();
+            (core::cmp::Reverse(count), first_seen(type_))
+        }),
+        UnionOrder::FirstSeen => {
+            branches.sort_by_key(|branch| first_seen(branch["type"].as_str().unwrap_or_default()))
         }
     }
 }
 
-/// Fill "properties" with collected values.
-/// Each property can be either of one type or multiple types joined via "anyOf"
-fn fill_properties(map: &mut Map<String, Value>, properties_types: &BTreeMap<String, Vec<&Value>>) {
-    let properties = map
-        .entry("properties")
-        .or_insert(json!({}))
-        .as_object_mut()
-        .unwrap();
-    for (property, known_types) in properties_types.iter() {
-        let types = {
-            if known_types.len() == 1 {
-                json!(known_types.first())
-            } else {
-                json!({ "anyOf": known_types })
-            }
-        };
-        properties.insert(property.clone(), types);
+/// Name of the JSON Schema type of the value
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if is_float(number) => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
     }
 }
 
-/// Infer a format of the given string.
-///
-/// Currently only the following formats are supported:
-///   - integer
-///   - date
-///   - date-time
-fn infer_format(string: &str) -> Option<&str> {
-    if string.parse::<i32>().is_ok() {
-        return Some("integer");
-    } else if NaiveDate::parse_from_str(string, "%Y-%m-%d").is_ok() {
-        return Some("date");
-    } else if DateTime::parse_from_rfc3339(string).is_ok() {
-        return Some("date-time");
+/// Remove schemas of item types whose share among the array items is below the threshold.
+/// Schemas of the most common type are kept even if it is below the threshold
+fn prune_rare_types(array: &[Value], items: &mut Vec<Value>, threshold: f64) {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for value in array {
+        *counts.entry(type_name(value)).or_default() += 1;
     }
-    None
+    let most_common = counts.values().copied().max().unwrap_or(0);
+    let total = array.len() as f64;
+    items.retain(|item| match item["type"].as_str() {
+        Some(type_) => {
+            let count = counts.get(type_).copied().unwrap_or(0);
+            count == most_common || count as f64 / total >= threshold
+        }
+        // Ignored values
+        None => true,
+    });
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Collect types of values described by the schema, including types of union branches
+fn collect_types<'s>(schema: &'s Value, types: &mut Vec<&'s str>) {
+    let mut add = |type_| {
+        if !types.contains(&type_) {
+            types.push(type_)
+        }
+    };
+    match schema.get("type") {
+        Some(Value::String(type_)) => add(type_.as_str()),
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).for_each(add),
+        _ => {}
+    }
+    for keyword in &["anyOf", "oneOf"] {
+        if let Some(Value::Array(branches)) = schema.get(keyword) {
+            for branch in branches {
+                collect_types(branch, types);
+            }
+        }
+    }
+}
 
-    fn assert_json(data: &[(Value, Value)]) {
-        for (value, expected) in data {
-            assert_eq!(infer(&value), *expected);
+/// JSON Schema draft to produce schemas for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Draft {
+    Draft4,
+    Draft6,
+    Draft7,
+    Draft201909,
+    Draft202012,
+}
+
+impl Draft {
+    /// URI of the draft's meta-schema, used in the `$schema` keyword
+    pub fn uri(&self) -> &'static str {
+        match self {
+            Draft::Draft4 => "http://json-schema.org/draft-04/schema#",
+            Draft::Draft6 => "http://json-schema.org/draft-06/schema#",
+            Draft::Draft7 => "http://json-schema.org/draft-07/schema#",
+            Draft::Draft201909 => "https://json-schema.org/draft/2019-09/schema",
+            Draft::Draft202012 => "https://json-schema.org/draft/2020-12/schema",
         }
     }
+}
 
-    #[test]
-    fn test_primitive_types() {
-        let cases = [
-            (
-                json!(null),
-                json!({"type": "null", "$schema": "http://json-schema.org/draft-07/schema#"}),
-            ),
-            (
-                json!(1.35),
-                json!({"type": "number", "$schema": "http://json-schema.org/draft-07/schema#"}),
-            ),
-            (
-                json!(5),
-                json!({"type": "integer", "$schema": "http://json-schema.org/draft-07/schema#"}),
-            ),
-            (
-                json!("Test".to_owned()),
-                json!({"type": "string", "$schema": "http://json-schema.org/draft-07/schema#"}),
-            ),
-        ];
-        assert_json(&cases);
+/// OpenAPI version to produce schemas for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum OpenApiVersion {
+    /// Schemas are based on Draft 4, with `nullable` instead of the `null` type
+    #[serde(rename = "3.0")]
+    V3_0,
+    /// Schemas are Draft 2020-12 schemas
+    #[serde(rename = "3.1")]
+    V3_1,
+}
+
+/// How schemas of different objects in the same array are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectMergeStrategy {
+    /// Merge them into a single object schema. Lossy, but compact
+    Merge,
+    /// Keep them as separate `anyOf` branches
+    Compose,
+}
+
+/// How branches of `anyOf` are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnionOrder {
+    /// Alphabetically by their types
+    TypeName,
+    /// The most common types of values first
+    Frequency,
+    /// In the order the types of values first appear in the input
+    FirstSeen,
+}
+
+/// How different formats of strings described by one schema are reconciled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FormatMerge {
+    /// Keep `format` only if all the strings have it
+    Intersect,
+    /// Describe strings that all have some format by `anyOf` of their formats
+    Union,
+}
+
+/// How types in arrays of types are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TypeArrayOrder {
+    /// Alphabetically, e.g. `["integer", "null", "string"]`
+    Alphabetical,
+    /// Alphabetically, but with `null` last, e.g. `["integer", "string", "null"]`
+    NullLast,
+}
+
+impl TypeArrayOrder {
+    fn sort(self, types: &mut [Value]) {
+        types.sort_by(|left, right| {
+            let (left, right) = (left.as_str(), right.as_str());
+            match self {
+                TypeArrayOrder::Alphabetical => left.cmp(&right),
+                TypeArrayOrder::NullLast => {
+                    (left == Some("null"), left).cmp(&(right == Some("null"), right))
+                }
+            }
+        })
     }
+}
 
-    #[test]
-    fn test_string_format() {
-        let cases = [
-            (
-                json!("1"),
-                json!({"type": "string", "format": "integer", "$schema": "http://json-schema.org/draft-07/schema#"}),
-            ),
-            (
-                json!("2020-01-01"),
-                json!({"type": "string", "format": "date", "$schema": "http://json-schema.org/draft-07/schema#"}),
-            ),
-            (
-                json!("2018-11-13T20:20:39+00:00"),
-                json!({"type": "string", "format": "date-time", "$schema": "http://json-schema.org/draft-07/schema#"}),
-            ),
-        ];
-        assert_json(&cases);
+/// `additionalProperties` of object schemas merged from schemas of different objects
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AdditionalProperties {
+    /// Any other properties are allowed. No keyword is emitted, as it is the default
+    Allow,
+    /// No other properties are allowed, i.e. `additionalProperties: false`
+    Deny,
+    /// Other properties are described by this schema, e.g. for map-like remainders
+    Schema(Value),
+}
+
+/// How items of arrays with many different schemas are described
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Fallback {
+    /// Items are described by `anyOf` of all the distinct schemas
+    AnyOf,
+    /// Items are described by `{}`, which accepts anything, if there are more distinct schemas
+    /// than the given number. With `0`, items of every non-empty array are described by `{}`
+    Permissive(usize),
+}
+
+/// How string lengths are measured for `minLength` and `maxLength`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthUnit {
+    /// Unicode code points, as JSON Schema validators count them
+    Chars,
+    /// UTF-16 code units, as JavaScript counts them
+    Utf16,
+    /// Bytes of the UTF-8 encoding
+    Bytes,
+    /// Extended grapheme clusters, i.e. user-perceived characters, so "e" with a combining
+    /// accent is one. Requires the `std` feature, as clusters are matched with `regex`
+    #[cfg(feature = "std")]
+    Graphemes,
+}
+
+impl LengthUnit {
+    /// Length of the string in this unit
+    fn measure(self, string: &str) -> usize {
+        match self {
+            LengthUnit::Chars => string.chars().count(),
+            LengthUnit::Utf16 => string.encode_utf16().count(),
+            LengthUnit::Bytes => string.len(),
+            #[cfg(feature = "std")]
+            LengthUnit::Graphemes => count_graphemes(string),
+        }
     }
+}
 
-    #[test]
-    fn test_disabled_string_format() {
-        let data = json!("2020-01-01");
-        let schema = JSONSchema::new(&data).detect_format(false);
-        assert_eq!(
-            schema.infer(),
-            json!({"type": "string", "$schema": "http://json-schema.org/draft-07/schema#"})
+/// Number of extended grapheme clusters, matched by the regex from UAX #29
+#[cfg(feature = "std")]
+fn count_graphemes(string: &str) -> usize {
+    static CLUSTER: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    let cluster = CLUSTER.get_or_init(|| {
+        let hangul = r"\p{gcb=L}*(?:\p{gcb=V}+|\p{gcb=LV}\p{gcb=V}*|\p{gcb=LVT})\p{gcb=T}*|\p{gcb=L}+|\p{gcb=T}+";
+        let pictographic = r"\p{Extended_Pictographic}(?:\p{gcb=Extend}*\p{gcb=ZWJ}\p{Extended_Pictographic})*";
+        let pattern = format!(
+            r"(?s)\r\n|\p{{gcb=Control}}|\p{{gcb=CR}}|\p{{gcb=LF}}|\p{{gcb=Prepend}}*(?:\p{{gcb=RI}}\p{{gcb=RI}}|{}|{}|[^\p{{gcb=Control}}\p{{gcb=CR}}\p{{gcb=LF}}])[\p{{gcb=Extend}}\p{{gcb=ZWJ}}\p{{gcb=SpacingMark}}]*",
+            hangul, pictographic
         );
+        regex::Regex::new(&pattern).expect("Invalid grapheme cluster pattern")
+    });
+    cluster.find_iter(string).count()
+}
+
+type Hook<'a> = Box<dyn Fn(&mut Value, &SchemaContext<'_>) + Send + Sync + 'a>;
+type FormatNameMapper<'a> = Box<dyn Fn(&str) -> String + Send + Sync + 'a>;
+type KeyPredicate<'a> = Box<dyn Fn(&str) -> bool + Send + Sync + 'a>;
+
+pub struct JSONSchema<'a> {
+    input: &'a Value,
+    config: Config,
+    hooks: Vec<Hook<'a>>,
+    format_name_mapper: Option<FormatNameMapper<'a>>,
+    write_only: Option<KeyPredicate<'a>>,
+    /// Formats that are detected, in the order they are checked. They are resolved from
+    /// the selected formats, the draft and `format_priority` whenever any of them changes,
+    /// so the order of builder calls doesn't matter
+    formats: Vec<Format>,
+    /// Whether shortcuts for repeated items and homogeneous scalar arrays are taken.
+    /// Tests disable them to compare with the general path
+    cache: bool,
+    /// Type conflicts for the strict mode and statistics, recorded during inference
+    recorder: Recorder,
+}
+
+impl<'a> JSONSchema<'a> {
+    pub fn new(input: &'a Value) -> JSONSchema<'a> {
+        JSONSchema::with_config(input, Config::default())
     }
 
-    #[test]
-    fn test_disabled_string_format_nested() {
-        let cases = [
-            (
-                json!({"key": "2020-01-01"}),
-                json!({"type": "object", "properties": {"key": {"type": "string"}}, "required": ["key"], "$schema": "http://json-schema.org/draft-07/schema#"}),
-            ),
-            (
-                json!(["2020-01-01"]),
-                json!({"type": "array", "items": {"type": "string"}, "$schema": "http://json-schema.org/draft-07/schema#"}),
-            ),
-        ];
-        for (value, expected) in &cases {
-            let schema = JSONSchema::new(&value).detect_format(false);
-            assert_eq!(schema.infer(), *expected);
+    /// Create an inference builder with the given configuration, e.g. loaded from a file
+    pub fn with_config(input: &'a Value, config: Config) -> JSONSchema<'a> {
+        JSONSchema {
+            input,
+            config,
+            hooks: vec![],
+            format_name_mapper: None,
+            write_only: None,
+            formats: vec![],
+            cache: true,
+            recorder: Recorder::default(),
         }
+        .resolve_formats()
     }
 
-    #[test]
-    fn test_array_primitive() {
-        let cases = [
-            (
-                json!(["test", "item"]),
-                json!({"type": "array", "items": {"type": "string"}, "$schema": "http://json-schema.org/draft-07/schema#"}),
-            ),
-            (
-                json!(["test", "item", 1]),
-                json!({
-                  "type": "array",
-                  "items": {
-                    "anyOf": [
-                      {"type": "string"},
-                      {"type": "integer"}
-                    ]
-                  },
-                  "$schema": "http://json-schema.org/draft-07/schema#"
-                }),
-            ),
-        ];
-        assert_json(&cases);
+    /// Formats to detect: the selected ones, `Format::Duration` since Draft 2019-09, and then
+    /// the formats named in `format_priority` moved first
+    fn resolve_formats(mut self) -> Self {
+        let mut formats = self.config.formats.clone();
+        if self.config.draft >= Draft::Draft201909 && !formats.contains(&Format::Duration) {
+            formats.push(Format::Duration);
+        }
+        let names = &self.config.format_priority;
+        // The sort is stable, so formats without priority keep their order
+        formats.sort_by_key(|format| {
+            names
+                .iter()
+                .position(|name| name == format.name())
+                .unwrap_or(names.len())
+        });
+        self.formats = formats;
+        self
     }
 
-    #[test]
-    fn test_object_primitive() {
-        let cases = [
-            (
-                json!({"key": true}),
-                json!({
-                  "type": "object",
-                  "properties": {
-                      "key": {"type": "boolean"}
-                  },
-                  "required": ["key"],
-                  "$schema": "http://json-schema.org/draft-07/schema#"
-                }),
-            ),
-            (
-                json!({"key1": true, "key2": 1}),
-                json!({
-                  "type": "object",
+    pub fn detect_format(mut self, detect_format: bool) -> Self {
+        self.config.detect_format = detect_format;
+        self
+    }
+
+    /// Set formats to detect, they are checked in the given order.
+    /// By default, these are `Format::DEFAULT`.
+    pub fn formats(mut self, formats: &[Format]) -> Self {
+        self.config.formats = formats.to_vec();
+        self.resolve_formats()
+    }
+
+    /// Detect only formats with the given names, e.g. `date-time`, checked in the given order.
+    /// Unlike `formats`, the names can come from runtime input. Unknown names are ignored.
+    pub fn only_formats(mut self, names: &[&str]) -> Self {
+        self.config.formats = names
+            .iter()
+            .filter_map(|name| Format::from_name(name))
+            .collect();
+        self.resolve_formats()
+    }
+
+    /// Add the input to `examples` of the root schema. Validators ignore it, but documentation
+    /// tools show it.
+    pub fn root_example(mut self, root_example: bool) -> Self {
+        self.config.root_example = root_example;
+        self
+    }
+
+    /// Add `x-generated-by` to the root schema with the name and the version of this crate,
+    /// and with the timestamp given by `generated_at`, if any.
+    pub fn emit_generator_info(mut self, emit_generator_info: bool) -> Self {
+        self.config.emit_generator_info = emit_generator_info;
+        self
+    }
+
+    /// The timestamp in `x-generated-by`, e.g. `2020-01-01T00:00:00Z`. There is none by default,
+    /// as schemas inferred from the same input would differ otherwise.
+    pub fn generated_at(mut self, timestamp: impl Into<String>) -> Self {
+        self.config.generated_at = Some(timestamp.into());
+        self
+    }
+
+    /// Check enabled formats with the given names first, in the given order, e.g. to report
+    /// `mailto:` URIs as `email` rather than `uri`. Other enabled formats keep their order after
+    /// them. It doesn't enable formats and applies to formats enabled before or after it.
+    /// Unknown names are ignored.
+    pub fn format_priority(mut self, names: &[&str]) -> Self {
+        self.config.format_priority = names.iter().map(|&name| name.to_owned()).collect();
+        self.resolve_formats()
+    }
+
+    /// Detect an additional format. It is checked after already enabled formats
+    pub fn enable_format(mut self, format: Format) -> Self {
+        if !self.config.formats.contains(&format) {
+            self.config.formats.push(format);
+        }
+        self.resolve_formats()
+    }
+
+    /// Treat objects with at least `threshold` keys as maps if all their values share one schema.
+    /// Such objects are inferred as `additionalProperties` instead of a fixed set of properties,
+    /// and if all keys have the same format, it is captured in `propertyNames`.
+    pub fn map_threshold(mut self, threshold: usize) -> Self {
+        self.config.map_threshold = Some(threshold);
+        self
+    }
+
+    /// Emit `propertyNames` with a pattern and `maxLength` for objects whose keys follow one
+    /// naming convention: snake_case, camelCase, kebab-case, PascalCase or SCREAMING_SNAKE_CASE.
+    /// Merged objects are described by all their keys. Keys of mixed conventions are skipped.
+    pub fn emit_property_names(mut self, emit_property_names: bool) -> Self {
+        self.config.emit_property_names = emit_property_names;
+        self
+    }
+
+    /// Describe keys of maps with at most `threshold` keys as `propertyNames` with `enum`
+    /// of the observed keys instead of a pattern. Takes effect only with `map_threshold`.
+    pub fn key_enum_threshold(mut self, threshold: usize) -> Self {
+        self.config.key_enum_threshold = Some(threshold);
+        self
+    }
+
+    /// Merge `integer` schemas into `number` ones when both appear among array items or
+    /// among the schemas of the same property, even if they have other keywords, e.g. `enum`.
+    /// Bare `integer` schemas are always merged into bare `number` ones.
+    pub fn unify_int_float(mut self, unify_int_float: bool) -> Self {
+        self.config.unify_int_float = unify_int_float;
+        self
+    }
+
+    /// Process arrays with more than `threshold` items in parallel. The default is 8.
+    /// Has no effect without the `parallel` feature.
+    pub fn parallel_threshold(mut self, threshold: usize) -> Self {
+        self.config.parallel_threshold = threshold;
+        self
+    }
+
+    /// List object keys in the `x-property-order` extension, in the order they appear in the input.
+    /// Merged objects list keys in the order of their first appearance among the merged schemas.
+    /// Note that `serde_json` keeps the document order of keys only with its `preserve_order`
+    /// feature enabled, otherwise keys are sorted.
+    pub fn emit_property_order(mut self, emit_property_order: bool) -> Self {
+        self.config.emit_property_order = emit_property_order;
+        self
+    }
+
+    /// Add `default` to scalar properties that have the same value in all objects where they
+    /// are present. Unlike `const`, it doesn't restrict the value and doesn't make the property
+    /// required.
+    pub fn detect_defaults(mut self, detect_defaults: bool) -> Self {
+        self.config.detect_defaults = detect_defaults;
+        self
+    }
+
+    /// Emit `enum` for scalar properties and array items that have at most `threshold` distinct
+    /// values. Detected formats are kept next to `enum`.
+    pub fn enum_threshold(mut self, threshold: usize) -> Self {
+        self.config.enum_threshold = Some(threshold);
+        self
+    }
+
+    /// Types of properties eligible for `enum` detection.
+    /// By default, these are "string", "integer" and "number".
+    pub fn enum_types(mut self, types: &[&str]) -> Self {
+        self.config.enum_types = types.iter().map(|&x| x.to_owned()).collect();
+        self
+    }
+
+    /// Register a function to post-process every inferred sub-schema, including the root one.
+    /// It is called with the schema and the context describing the value it was inferred from.
+    /// Hooks run in the order of registration, before schemas of array items are merged.
+    pub fn hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&mut Value, &SchemaContext<'_>) + Send + Sync + 'a,
+    {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Always express `type` as an array, e.g. `["integer"]` instead of `"integer"`.
+    pub fn type_as_array(mut self, type_as_array: bool) -> Self {
+        self.config.type_as_array = type_as_array;
+        self
+    }
+
+    /// Add `minimum: 0` to integer properties if all their observed values are non-negative.
+    pub fn assume_unsigned(mut self, assume_unsigned: bool) -> Self {
+        self.config.assume_unsigned = assume_unsigned;
+        self
+    }
+
+    /// Add an exclusive lower bound of 0 to numeric properties if all their observed values are
+    /// positive. It is `exclusiveMinimum: 0` or, in Draft 4, `minimum: 0` with
+    /// `exclusiveMinimum: true`.
+    pub fn assume_positive(mut self, assume_positive: bool) -> Self {
+        self.config.assume_positive = assume_positive;
+        self
+    }
+
+    /// Set how schemas of different objects in the same array are combined.
+    /// The default is `ObjectMergeStrategy::Merge`.
+    pub fn object_merge_strategy(mut self, strategy: ObjectMergeStrategy) -> Self {
+        self.config.object_merge_strategy = strategy;
+        self
+    }
+
+    /// Set the JSON Schema draft to produce schemas for. The default is `Draft::Draft7`.
+    /// Draft 2019-09 and newer ones also enable detection of `Format::Duration`, after the
+    /// selected formats, even if they are selected later via `formats`.
+    pub fn draft(mut self, draft: Draft) -> Self {
+        self.config.draft = draft;
+        self.resolve_formats()
+    }
+
+    /// Use a custom meta-schema URI in `$schema`. It takes precedence over the draft's URI.
+    pub fn schema_uri(mut self, uri: impl Into<String>) -> Self {
+        self.config.schema_uri = Some(uri.into());
+        self
+    }
+
+    /// Collect up to `limit` distinct values of strings, numbers and booleans as `examples`.
+    /// Examples are collected the same way for array items and object properties, and each
+    /// `anyOf` branch of a union has the examples of its own type.
+    pub fn examples(mut self, limit: usize) -> Self {
+        self.config.examples = limit;
+        self
+    }
+
+    /// Describe empty objects as `{"type": "object"}`, which accepts any object, instead of
+    /// an object with no properties. When merged with other objects, an empty object is
+    /// treated as one without properties, so it makes all merged properties optional.
+    pub fn open_empty_objects(mut self, open_empty_objects: bool) -> Self {
+        self.config.open_empty_objects = open_empty_objects;
+        self
+    }
+
+    /// Measure string lengths for `minLength` and `maxLength` in the given unit.
+    /// By default, `LengthUnit::Chars`. Validators count code points, so bounds in other units
+    /// are meant for consumers that count the same way, e.g. databases with byte limits.
+    pub fn length_unit(mut self, unit: LengthUnit) -> Self {
+        self.config.length_unit = unit;
+        self
+    }
+
+    /// Set `additionalProperties` of object schemas that are merged from schemas of different
+    /// objects, e.g. objects with different keys in one array. Schemas of single objects are not
+    /// affected. By default, `AdditionalProperties::Allow`.
+    pub fn merged_additional_properties(mut self, additional: AdditionalProperties) -> Self {
+        self.config.merged_additional_properties = additional;
+        self
+    }
+
+    /// Mark arrays of integers from 0 to 255 as `x-binary: true`, as they likely represent bytes,
+    /// and restrict their items to this range. Any such array matches, e.g. `[1, 2, 3]` too.
+    pub fn detect_byte_arrays(mut self, detect_byte_arrays: bool) -> Self {
+        self.config.detect_byte_arrays = detect_byte_arrays;
+        self
+    }
+
+    /// Describe arrays of tagged objects as `oneOf` with a schema per tag value.
+    /// The tag is a string property present in every object, named by `key` or, if it is `None`,
+    /// detected as one with a few distinct values that correspond to different sets of properties.
+    /// With an explicit `key`, arrays with a single tag value are described as tagged unions too,
+    /// so that tagged unions of different arrays, e.g. in different objects, are merged per tag.
+    pub fn discriminator(mut self, key: Option<&str>) -> Self {
+        self.config.discriminate = true;
+        self.config.discriminator = key.map(str::to_owned);
+        self
+    }
+
+    /// Emit `minLength` and `maxLength` with the shortest and the longest observed strings.
+    /// Lengths are counted in Unicode code points, unless `length_unit` says otherwise.
+    pub fn string_length_bounds(mut self, string_length_bounds: bool) -> Self {
+        self.config.string_length_bounds = string_length_bounds;
+        self
+    }
+
+    /// Never emit `maxLength` greater than `cap`, so a single huge string doesn't produce
+    /// an absurd bound. Longer strings count as `cap` long, for `minLength` too, so the bounds
+    /// stay satisfiable.
+    /// Takes effect only with `string_length_bounds`.
+    pub fn max_length_cap(mut self, cap: usize) -> Self {
+        self.config.max_length_cap = Some(cap);
+        self
+    }
+
+    /// Emit `minimum` and `maximum` with the smallest and the largest observed numbers.
+    pub fn number_bounds(mut self, number_bounds: bool) -> Self {
+        self.config.number_bounds = number_bounds;
+        self
+    }
+
+    /// Round `minimum` down and `maximum` up to the given number of decimal places, so noisy
+    /// observations like `99.97` give `100.0` with 0 places. Rounding is outward, so the bounds
+    /// still accept all observed numbers. Takes effect only with `number_bounds`.
+    pub fn bounds_rounding(mut self, places: u32) -> Self {
+        self.config.bounds_rounding = Some(places);
+        self
+    }
+
+    /// Emit `minItems` and `maxItems` with the numbers of items of the shortest and the longest
+    /// observed arrays.
+    pub fn array_length_bounds(mut self, array_length_bounds: bool) -> Self {
+        self.config.array_length_bounds = array_length_bounds;
+        self
+    }
+
+    /// Never emit `maxItems` greater than `cap`, so a single huge array doesn't produce an absurd
+    /// bound. `minItems` stays at the observed length. Takes effect only with
+    /// `array_length_bounds`.
+    pub fn max_items_cap(mut self, cap: usize) -> Self {
+        self.config.max_items_cap = Some(cap);
+        self
+    }
+
+    /// Remap names of detected formats before they are emitted, e.g. for validators with
+    /// a nonstandard format vocabulary.
+    pub fn format_name_mapper<F>(mut self, mapper: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'a,
+    {
+        self.format_name_mapper = Some(Box::new(mapper));
+        self
+    }
+
+    /// Mark properties whose names match the predicate with `writeOnly: true`, e.g. passwords.
+    /// The keyword is defined since Draft 7, therefore it is not emitted for older drafts.
+    pub fn mark_write_only<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'a,
+    {
+        self.write_only = Some(Box::new(predicate));
+        self
+    }
+
+    /// Describe in `$comment` how merged, union and map schemas were derived.
+    /// Validators ignore `$comment`, so it is only useful for debugging.
+    pub fn annotate_provenance(mut self, annotate_provenance: bool) -> Self {
+        self.config.annotate_provenance = annotate_provenance;
+        self
+    }
+
+    /// Emit `required` in object schemas. If disabled, all properties are optional
+    /// at every nesting level.
+    pub fn emit_required(mut self, emit_required: bool) -> Self {
+        self.config.emit_required = emit_required;
+        self
+    }
+
+    /// Emit `required` only in object schemas nested in at most `max_depth` objects, so with 0
+    /// only the root object, or objects in the root array, have it. Properties of deeper
+    /// objects are all optional.
+    pub fn required_max_depth(mut self, max_depth: usize) -> Self {
+        self.config.required_max_depth = Some(max_depth);
+        self
+    }
+
+    /// Move object schemas that occur more than once to `$defs`, or to `definitions` before
+    /// Draft 2019-09, and reference them via `$ref`. Definitions are named by a hash of their
+    /// content, so the same input always produces the same names.
+    pub fn extract_definitions(mut self, extract_definitions: bool) -> Self {
+        self.config.extract_definitions = extract_definitions;
+        self
+    }
+
+    /// Describe strings containing a JSON object or array with `contentMediaType: application/json`
+    /// and, since Draft 2019-09, their inferred schema in `contentSchema`.
+    /// Older drafts have no such keywords, so these strings are described as plain strings there.
+    pub fn detect_embedded_json(mut self, detect_embedded_json: bool) -> Self {
+        self.config.detect_embedded_json = detect_embedded_json;
+        self
+    }
+
+    /// Treat properties that differ only in case, e.g. `Email` and `email`, as the same property
+    /// when merging objects. The name seen first in the input is used.
+    pub fn case_insensitive_keys(mut self, case_insensitive_keys: bool) -> Self {
+        self.config.case_insensitive_keys = case_insensitive_keys;
+        self
+    }
+
+    /// Describe recursive structures, e.g. trees, with a definition that references itself.
+    /// An object schema is considered recursive if it contains an object schema with the same
+    /// properties; nested objects with a subset of them become references too.
+    pub fn detect_recursion(mut self, detect_recursion: bool) -> Self {
+        self.config.detect_recursion = detect_recursion;
+        self
+    }
+
+    /// Don't infer schemas for values at the given JSON Pointers, they are described by `{}`,
+    /// which accepts any value. A `*` reference token matches any key or index,
+    /// e.g. `/items/*/raw`.
+    pub fn ignore_paths(mut self, paths: &[&str]) -> Self {
+        self.config.ignore_paths = paths.iter().map(|&path| path.to_owned()).collect();
+        self
+    }
+
+    /// Fail with `InferError::TypeConflict` instead of joining schemas of different types
+    /// via `anyOf`, to catch inconsistent data. Joins that don't produce `anyOf` fail too, e.g.
+    /// with `max_union_branches`, `Fallback::Permissive` or `item_type_threshold`, and so do
+    /// integers joined with numbers, unless `unify_int_float` is set.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.config.strict = strict;
+        self
+    }
+
+    /// Describe boolean properties that have the same value in all samples with `const`,
+    /// or with a single-value `enum` for Draft 4.
+    pub fn detect_bool_const(mut self, detect_bool_const: bool) -> Self {
+        self.config.detect_bool_const = detect_bool_const;
+        self
+    }
+
+    /// Don't describe array items of types that make up less than `threshold` of the items,
+    /// e.g. a stray `null` among thousands of integers with a threshold of 0.01.
+    /// Items of the most common type are always described.
+    pub fn item_type_threshold(mut self, threshold: f64) -> Self {
+        self.config.item_type_threshold = Some(threshold);
+        self
+    }
+
+    /// Infer schemas of items of arrays larger than `size` only from `size` items chosen
+    /// pseudo-randomly. The choice depends only on `seed`, so the output is reproducible.
+    /// It is much faster on large arrays, but rare shapes of items are likely to be missed,
+    /// and bounds or enums describe only the chosen items. A size of 0 disables sampling, as
+    /// no items can't describe a non-empty array.
+    pub fn sample_size(mut self, size: usize) -> Self {
+        self.config.sample_size = Some(size);
+        self
+    }
+
+    /// Set the seed of all pseudo-random decisions, currently only sampling via `sample_size`.
+    /// Inference with the same seed and settings always gives the same schema.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        self
+    }
+
+    /// Mark properties that are not required with `x-optional: true`, for tools that don't read
+    /// `required`. With `emit_required(false)`, the annotations replace `required`.
+    pub fn optional_annotation(mut self, optional_annotation: bool) -> Self {
+        self.config.optional_annotation = optional_annotation;
+        self
+    }
+
+    /// Omit keywords that don't constrain anything, e.g. empty `required` or `properties` and
+    /// `items` that accept any value. The schema accepts the same values as without this option.
+    pub fn minimal(mut self, minimal: bool) -> Self {
+        self.config.minimal = minimal;
+        self
+    }
+
+    /// Join schemas of the same type that differ only in `format` before building `anyOf`,
+    /// e.g. dates and plain strings become strings without a format, and replace `anyOf` and
+    /// `oneOf` with a single branch by the branch. Enabled by default.
+    pub fn simplify_unions(mut self, simplify_unions: bool) -> Self {
+        self.config.simplify_unions = simplify_unions;
+        self
+    }
+
+    /// Describe arrays as tuples, with a schema for every position and no other items,
+    /// via `prefixItems` since Draft 2020-12 and via an array of `items` before.
+    /// Tuples from different samples are not merged.
+    pub fn tuple_arrays(mut self, tuple_arrays: bool) -> Self {
+        self.config.tuple_arrays = tuple_arrays;
+        self
+    }
+
+    /// Treat arrays as sets: their order is not significant, so they are described by one
+    /// `items` schema even if `tuple_arrays` is set, and `uniqueItems: true` is added
+    /// if there are no repeated items.
+    pub fn set_semantics(mut self, set_semantics: bool) -> Self {
+        self.config.set_semantics = set_semantics;
+        self
+    }
+
+    /// Order `anyOf` branches of array items and merged properties by their types.
+    /// Frequencies of property types count merged object schemas, not objects, as identical
+    /// objects are inferred only once.
+    pub fn union_order(mut self, order: UnionOrder) -> Self {
+        self.config.union_order = Some(order);
+        self
+    }
+
+    /// How strings with different formats are described by one schema, e.g. in merged objects.
+    /// `FormatMerge::Intersect` keeps `format` only if every string has it, and
+    /// `FormatMerge::Union` keeps each format in `anyOf` if every string has some format.
+    /// Non-empty strings without a format make it dropped either way, as the schema has to accept
+    /// them. Empty strings are skipped, as they match no format. Only applies if `simplify_unions`
+    /// is set.
+    pub fn format_merge(mut self, strategy: FormatMerge) -> Self {
+        self.config.format_merge = strategy;
+        self
+    }
+
+    /// Describe unions of schemas that only have a type, e.g. `anyOf` of `integer` and `null`,
+    /// by one array of types ordered by `order`, so the output doesn't depend on the order of
+    /// inference or on `union_order`.
+    pub fn type_array_order(mut self, order: TypeArrayOrder) -> Self {
+        self.config.type_array_order = Some(order);
+        self
+    }
+
+    /// Describe array items by `{}` with `Fallback::Permissive` if they have too many different
+    /// schemas, which is simpler for lenient consumers than a large `anyOf`.
+    /// By default, it is `Fallback::AnyOf`, i.e. all the schemas are listed.
+    pub fn array_items_fallback(mut self, fallback: Fallback) -> Self {
+        self.config.array_items_fallback = fallback;
+        self
+    }
+
+    /// Describe unions of more than `max_branches` schemas only by their types, e.g.
+    /// `{"type": ["integer", "object"]}`, or by `{}` if some branches have no single type,
+    /// instead of listing all the branches in `anyOf`. It bounds the size of schemas for inputs
+    /// with many different shapes, e.g. with `ObjectMergeStrategy::Compose`.
+    pub fn max_union_branches(mut self, max_branches: usize) -> Self {
+        self.config.max_union_branches = Some(max_branches);
+        self
+    }
+
+    /// Fail with `InferError::ScalarRoot` if the input is neither an object nor an array,
+    /// e.g. to catch scalars passed by accident.
+    pub fn require_composite_root(mut self, require_composite_root: bool) -> Self {
+        self.config.require_composite_root = require_composite_root;
+        self
+    }
+
+    /// Fail with `InferError::ForbiddenType` on values of the given types, e.g. `null`.
+    /// The names are those of the inferred `type` keywords, so `number` doesn't cover integers.
+    /// Values at ignored paths are not checked.
+    pub fn forbidden_types(mut self, types: &[&str]) -> Self {
+        self.config.forbidden_types = types.iter().map(|&type_| type_.to_owned()).collect();
+        self
+    }
+
+    /// How strictly strings are checked before they are reported as `email` or `idn-email`.
+    /// By default, `EmailStrictness::Strict`. Has effect only if these formats are detected.
+    pub fn email_validation(mut self, strictness: EmailStrictness) -> Self {
+        self.config.email_validation = strictness;
+        self
+    }
+
+    /// Produce schemas for OpenAPI documents: without `$schema` and, for OpenAPI 3.0, with
+    /// `nullable: true` instead of unions with `null`, `example` instead of `examples` and
+    /// `format: byte` instead of `contentEncoding: base64`. Sets the closest JSON Schema draft.
+    pub fn openapi(mut self, version: OpenApiVersion) -> Self {
+        self.config.openapi = Some(version);
+        self.draft(match version {
+            OpenApiVersion::V3_0 => Draft::Draft4,
+            OpenApiVersion::V3_1 => Draft::Draft202012,
+        })
+    }
+
+    /// Infer a schema for the input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input can't be described by a schema. See `try_infer` for details.
+    pub fn infer(&self) -> Value {
+        self.try_infer().expect("Inference failed")
+    }
+
+    /// Infer a schema for the input.
+    /// Fails if the strict mode finds values of different types, if there is a value of a forbidden type, or if the input is a scalar
+    /// while `require_composite_root` is set
+    pub fn try_infer(&self) -> Result<Value, InferError> {
+        self.infer_recorded(false).map(|(schema, _)| schema)
+    }
+
+    /// Infer a schema, recording type conflicts for the strict mode and statistics if `stats`
+    /// is set. Conflicts are recorded wherever schemas of different types are joined, including
+    /// joins that don't end up as `anyOf`, e.g. collapsed unions or pruned rare types
+    fn infer_recorded(&self, stats: bool) -> Result<(Value, Collector), InferError> {
+        if self.config.require_composite_root && !self.input.is_object() && !self.input.is_array() {
+            return Err(InferError::ScalarRoot {
+                type_name: type_name(self.input).to_owned(),
+            });
+        }
+        let (mut result, stats) =
+            self.recorded(stats, || self._infer(self.input, &Location::Root))?;
+        self.finalize(&mut result);
+        if self.config.root_example {
+            if let Value::Object(map) = &mut result {
+                map.insert("examples".into(), json!([self.input]));
+            }
+        }
+        if self.config.emit_generator_info {
+            if let Value::Object(map) = &mut result {
+                let mut info = json!({
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION")
+                });
+                if let Some(timestamp) = &self.config.generated_at {
+                    info["timestamp"] = Value::String(timestamp.clone());
+                }
+                map.insert("x-generated-by".into(), info);
+            }
+        }
+        if let Some(version) = self.config.openapi {
+            openapi::to_openapi(&mut result, version);
+            return Ok((result, stats));
+        }
+        let uri = match &self.config.schema_uri {
+            Some(uri) => uri.clone(),
+            None => self.config.draft.uri().to_owned(),
+        };
+        set_schema_uri(&mut result, uri);
+        Ok((result, stats))
+    }
+
+    /// Post-process the whole inferred schema
+    fn finalize(&self, schema: &mut Value) {
+        remove_empty_string_markers(schema);
+        if self.config.detect_recursion {
+            self.hoist_recursive(schema);
+        }
+        if self.config.extract_definitions {
+            self.hoist_definitions(schema);
+        }
+        if self.config.optional_annotation {
+            let emit_required = self.config.emit_required;
+            walk::walk_mut(schema, &mut |schema| {
+                annotate_optional(schema, emit_required)
+            });
+        }
+        if self.config.minimal {
+            walk::walk_mut(schema, &mut remove_redundant_keywords);
+        }
+        if self.config.simplify_unions {
+            walk::walk_mut(schema, &mut flatten_single_branch);
+        }
+        if let Some(order) = self.config.type_array_order {
+            walk::walk_mut(schema, &mut |schema| collapse_type_union(schema, order));
+        }
+        if self.config.type_as_array {
+            walk::walk_mut(schema, &mut |schema| {
+                if let Some(type_) = schema.get_mut("type") {
+                    if type_.is_string() {
+                        *type_ = Value::Array(vec![type_.take()]);
+                    }
+                }
+            });
+        }
+    }
+
+    /// Move object schemas that occur more than once to definitions and replace them with `$ref`.
+    /// Definitions are named by a hash of their content, so names are stable across runs.
+    /// At most `MAX_DEFINITIONS` of the most frequent schemas are moved, the rest stay in place
+    fn hoist_definitions(&self, schema: &mut Value) {
+        let mut counts: BTreeMap<String, (usize, Value)> = BTreeMap::new();
+        walk::subschemas_mut(&mut schema.clone(), &mut |subschema| {
+            walk::walk_mut(subschema, &mut |subschema| {
+                if subschema["type"] == "object" {
+                    counts
+                        .entry(subschema.to_string())
+                        .or_insert_with(|| (0, subschema.clone()))
+                        .0 += 1;
+                }
+            })
+        });
+        let keyword = self.definitions_keyword();
+        let mut names: BTreeMap<String, String> = BTreeMap::new();
+        let mut definitions = Map::new();
+        let mut repeated: Vec<_> = counts
+            .into_iter()
+            .filter(|(_, (count, _))| *count > 1)
+            .collect();
+        // Stable, as ties keep the order of canonical forms
+        repeated.sort_by(|(_, (left, _)), (_, (right, _))| right.cmp(left));
+        repeated.truncate(MAX_DEFINITIONS);
+        for (canonical, (_, definition)) in repeated {
+            let hash = fnv1a(canonical.as_bytes());
+            let mut name = format!("object-{:016x}", hash);
+            let mut suffix = 1;
+            while definitions.contains_key(&name) {
+                suffix += 1;
+                name = format!("object-{:016x}-{}", hash, suffix);
+            }
+            definitions.insert(name.clone(), definition);
+            names.insert(canonical, name);
+        }
+        if definitions.is_empty() {
+            return;
+        }
+        let mut replace = |subschema: &mut Value| {
+            walk::walk_top_down_mut(subschema, &mut |subschema| match names
+                .get(&subschema.to_string())
+            {
+                Some(name) => {
+                    *subschema = json!({ "$ref": format!("#/{}/{}", keyword, name) });
+                    false
+                }
+                None => true,
+            })
+        };
+        walk::subschemas_mut(schema, &mut replace);
+        // Definitions may contain other repeated schemas
+        for definition in definitions.values_mut() {
+            walk::subschemas_mut(definition, &mut replace);
+        }
+        add_definitions(schema, keyword, definitions);
+    }
+
+    /// Replace object schemas that contain object schemas with the same properties by a recursive
+    /// definition. Nested objects with a subset of these properties, e.g. leaves of a tree,
+    /// are described by the definition too
+    fn hoist_recursive(&self, schema: &mut Value) {
+        let keyword = self.definitions_keyword();
+        if let Some(definition) = recursive_definition(schema, &json!({"$ref": "#"})) {
+            *schema = definition;
+        }
+        let mut definitions = Map::new();
+        walk::subschemas_mut(schema, &mut |subschema| {
+            walk::walk_top_down_mut(subschema, &mut |subschema| {
+                let name = format!("object-{:016x}", fnv1a(subschema.to_string().as_bytes()));
+                let reference = json!({ "$ref": format!("#/{}/{}", keyword, name) });
+                match recursive_definition(subschema, &reference) {
+                    Some(definition) => {
+                        definitions.insert(name, definition);
+                        *subschema = reference;
+                        false
+                    }
+                    None => true,
+                }
+            })
+        });
+        add_definitions(schema, keyword, definitions);
+    }
+
+    /// Keyword for definitions in the selected draft
+    fn definitions_keyword(&self) -> &'static str {
+        if self.config.draft >= Draft::Draft201909 {
+            "$defs"
+        } else {
+            "definitions"
+        }
+    }
+
+    /// Run an inference in a new recorder session, so records of concurrent inferences with the
+    /// same settings don't mix. Fails if the strict mode recorded type conflicts
+    fn recorded<T>(
+        &self,
+        stats: bool,
+        infer: impl FnOnce() -> Result<T, InferError>,
+    ) -> Result<(T, Collector), InferError> {
+        let _session = self.recorder.start(stats);
+        let result = infer();
+        let mut records = self.recorder.finish();
+        let result = result?;
+        let conflicts = records.conflicts();
+        if !conflicts.is_empty() {
+            return Err(InferError::TypeConflict { conflicts });
+        }
+        Ok((result, records.stats))
+    }
+
+    /// Refine an existing schema so it also accepts the input.
+    /// New properties are added, required properties missing in the input become optional,
+    /// and keywords that inference doesn't produce, like `description`, are preserved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input can't be described by a schema. See `try_infer` for details.
+    pub fn refine(&self, existing: &Value) -> Value {
+        let (mut inferred, _) = self
+            .recorded(false, || self._infer(self.input, &Location::Root))
+            .expect("Inference failed");
+        remove_empty_string_markers(&mut inferred);
+        refine::refine_schema(existing, &inferred)
+    }
+
+    /// Infer a schema and collect statistics about the input and the inference process.
+    /// Properties of merged objects are explained by how many distinct schemas their values have
+    /// and why they are optional, which helps to find over-merged schemas.
+    /// Statistics are collected while the schema is inferred, so every value is inferred once,
+    /// at its actual location. Fails in the same cases as `try_infer`
+    pub fn infer_with_stats(&self) -> Result<(Value, InferStats), InferError> {
+        let (schema, stats) = self.infer_recorded(true)?;
+        let stats = stats.finish(&schema);
+        Ok((schema, stats))
+    }
+
+    fn _infer(&self, data: &Value, location: &Location) -> Result<Value, InferError> {
+        let schema = self.infer_value(data, location)?;
+        if self.recorder.collects_stats() {
+            let path = location.path();
+            let parent = match location {
+                Location::Key(parent, _) => Some(parent.path()),
+                _ => None,
+            };
+            let (value_hash, schema_hash) = (canonical_hash(data), canonical_hash(&schema));
+            self.recorder.observe(|stats| {
+                if data.is_object() {
+                    stats.observe_object(path.clone());
+                }
+                stats.observe(path, parent, value_hash, schema_hash);
+            });
+        }
+        Ok(schema)
+    }
+
+    /// Record a type conflict for the strict mode if the schemas describe values of different
+    /// types. Integers are numbers if `unify_int_float` is set
+    fn record_conflict<'s>(
+        &self,
+        location: &Location,
+        schemas: impl IntoIterator<Item = &'s Value>,
+    ) {
+        if !self.config.strict {
+            return;
+        }
+        let mut types = vec![];
+        for schema in schemas {
+            collect_types(schema, &mut types);
+        }
+        let mut distinct = vec![];
+        for type_ in types {
+            let type_ = if self.config.unify_int_float && type_ == "integer" {
+                "number"
+            } else {
+                type_
+            };
+            if !distinct.contains(&type_) {
+                distinct.push(type_);
+            }
+        }
+        if distinct.len() > 1 {
+            self.recorder.conflict(location.path(), distinct);
+        }
+    }
+
+    /// Whether shortcuts that skip inference of some values are taken, they are not when
+    /// statistics count all the values
+    fn shortcuts(&self) -> bool {
+        self.cache && !self.recorder.collects_stats()
+    }
+
+    fn infer_value(&self, data: &Value, location: &Location) -> Result<Value, InferError> {
+        if !self.config.ignore_paths.is_empty() {
+            let pointer = location.pointer();
+            if self
+                .config
+                .ignore_paths
+                .iter()
+                .any(|pattern| pointer_matches(pattern, &pointer))
+            {
+                return Ok(json!({}));
+            }
+        }
+        if !self.config.forbidden_types.is_empty() {
+            let type_ = type_name(data);
+            if self.config.forbidden_types.iter().any(|name| name == type_) {
+                return Err(InferError::ForbiddenType {
+                    pointer: location.pointer(),
+                    type_name: type_.to_owned(),
+                });
+            }
+        }
+        let mut schema = match data {
+            Value::Null => json!({"type": "null"}),
+            Value::Bool(_) => json!({"type": "boolean"}),
+            Value::String(string) => {
+                let mut schema = match self.infer_embedded_json(string, location)? {
+                    Some(schema) => schema,
+                    None => self.infer_string(string, self.config.detect_format),
+                };
+                if self.config.string_length_bounds {
+                    let length = self.config.length_unit.measure(string);
+                    // Both bounds are capped, so `minLength` never exceeds `maxLength`
+                    let length = self
+                        .config
+                        .max_length_cap
+                        .map_or(length, |cap| length.min(cap));
+                    schema["minLength"] = json!(length);
+                    schema["maxLength"] = json!(length);
+                }
+                schema
+            }
+            Value::Number(number) => {
+                let mut schema = infer_number(number);
+                if self.config.number_bounds {
+                    let (minimum, maximum) = self.number_bounds_of(number);
+                    schema["minimum"] = minimum;
+                    schema["maximum"] = maximum;
+                }
+                schema
+            }
+            Value::Array(array) => self.infer_array(array, location)?,
+            Value::Object(object) => self.infer_object(object, location)?,
+        };
+        if self.config.examples > 0 && !data.is_null() && !data.is_array() && !data.is_object() {
+            schema["examples"] = json!([data]);
+        }
+        if !self.hooks.is_empty() {
+            let context = SchemaContext {
+                pointer: location.pointer(),
+                value: data,
+            };
+            for hook in &self.hooks {
+                hook(&mut schema, &context);
+            }
+        }
+        if data.as_str() == Some("") && self.config.detect_format && self.config.simplify_unions {
+            schema[EMPTY_STRING_MARKER] = Value::Bool(true);
+        }
+        Ok(schema)
+    }
+
+    fn infer_string(&self, string: &str, detect_format: bool) -> Value {
+        let mut data = json!({"type": "string"});
+        if detect_format {
+            if let Some(format_name) =
+                infer_format(string, &self.formats, self.config.email_validation)
+            {
+                data["format"] = match &self.format_name_mapper {
+                    Some(mapper) => Value::String(mapper(format_name)),
+                    None => Value::String(format_name.into()),
+                };
+            }
+        }
+        data
+    }
+
+    /// Infer schema for a string containing a JSON object or array, if `detect_embedded_json` is set
+    fn infer_embedded_json(
+        &self,
+        string: &str,
+        location: &Location,
+    ) -> Result<Option<Value>, InferError> {
+        if !self.config.detect_embedded_json || !string.trim_start().starts_with(['{', '[']) {
+            return Ok(None);
+        }
+        let content: Value = match serde_json::from_str(string) {
+            Ok(content) => content,
+            Err(_) => return Ok(None),
+        };
+        let mut schema = json!({"type": "string"});
+        if self.config.draft >= Draft::Draft7 {
+            schema["contentMediaType"] = Value::String("application/json".into());
+        }
+        if self.config.draft >= Draft::Draft201909 {
+            schema["contentSchema"] = self._infer(&content, location)?;
+        }
+        Ok(Some(schema))
+    }
+
+    /// Bounds of a single number, rounded outward as `bounds_rounding` says.
+    /// Integers are never rounded, as they have no decimal places
+    fn number_bounds_of(&self, number: &Number) -> (Value, Value) {
+        let exact = Value::Number(number.clone());
+        match (self.config.bounds_rounding, number.as_f64()) {
+            (Some(places), Some(value)) if is_float(number) => {
+                let scale =
+                    (0..places.min(f64::MAX_10_EXP as u32)).fold(1.0, |scale, _| scale * 10.0);
+                // `value * scale` is rounded itself, so the bound may end up a unit too far inward
+                let units = floor(value * scale);
+                let mut minimum = units / scale;
+                if minimum > value {
+                    minimum = (units - 1.0) / scale;
+                }
+                let units = -floor(-value * scale);
+                let mut maximum = units / scale;
+                if maximum < value {
+                    maximum = (units + 1.0) / scale;
+                }
+                // Very large scales overflow, the exact value is the tightest bound then
+                let round = |bound: f64| {
+                    Number::from_f64(bound)
+                        .filter(|_| bound.is_finite())
+                        .map_or_else(|| exact.clone(), Value::Number)
+                };
+                (round(minimum), round(maximum))
+            }
+            _ => (exact.clone(), exact),
+        }
+    }
+
+    /// Infer schema for an array
+    fn infer_array(&self, array: &[Value], location: &Location) -> Result<Value, InferError> {
+        if self.config.tuple_arrays && !self.config.set_semantics && !array.is_empty() {
+            return self.infer_tuple(array, location);
+        }
+        let mut data = json!({"type": "array"});
+        data["items"] = self.infer_array_items(array, location)?;
+        if self.config.detect_byte_arrays
+            && is_byte_array(array)
+            && data["items"]["type"] == "integer"
+        {
+            data["items"]["minimum"] = json!(0);
+            data["items"]["maximum"] = json!(255);
+            data["x-binary"] = Value::Bool(true);
+        }
+        if self.config.set_semantics && has_unique_items(array) {
+            data["uniqueItems"] = Value::Bool(true);
+        }
+        if self.config.array_length_bounds {
+            data["minItems"] = json!(array.len());
+            data["maxItems"] = json!(self
+                .config
+                .max_items_cap
+                .map_or(array.len(), |cap| array.len().min(cap)));
+        }
+        Ok(data)
+    }
+
+    /// Infer one schema for all array items
+    fn infer_array_items(&self, array: &[Value], location: &Location) -> Result<Value, InferError> {
+        if self.config.discriminate {
+            if let Some(items) = self.infer_tagged_union(array, location)? {
+                return Ok(items);
+            }
+        }
+        if let Some(items) = self.infer_homogeneous_items(array) {
+            return Ok(items);
+        }
+        let items = self.infer_items(array, location)?;
+        let mut items: Vec<Value> = if self.config.case_insensitive_keys {
+            // Objects are merged in the order of the items, so the first seen casing of a
+            // property name is kept
+            let mut seen = BTreeSet::new();
+            items
+                .into_iter()
+                .filter(|(hash, _)| seen.insert(*hash))
+                .map(|(_, schema)| schema)
+                .collect()
+        } else {
+            // Equal schemas are kept once
+            items
+                .into_iter()
+                .collect::<BTreeMap<u64, Value>>()
+                .into_values()
+                .collect()
+        };
+        let items_location = Location::Items(location);
+        if let Some(threshold) = self.config.item_type_threshold {
+            // Pruned types conflict with the kept ones too
+            self.record_conflict(&items_location, &items);
+            prune_rare_types(array, &mut items, threshold);
+        }
+        let observed: Vec<&str> = if self.config.union_order.is_some() {
+            array.iter().map(type_name).collect()
+        } else {
+            vec![]
+        };
+        Ok(self.join_items(items, &observed, &items_location))
+    }
+
+    /// Infer a schema for every position of the array, no other items are allowed
+    fn infer_tuple(&self, array: &[Value], location: &Location) -> Result<Value, InferError> {
+        let mut items = Vec::with_capacity(array.len());
+        for (idx, item) in array.iter().enumerate() {
+            items.push(self._infer(item, &Location::Index(location, idx))?);
+        }
+        if self.config.draft >= Draft::Draft202012 {
+            Ok(json!({"type": "array", "prefixItems": items, "items": false}))
+        } else {
+            Ok(json!({"type": "array", "items": items, "additionalItems": false}))
+        }
+    }
+
+    /// Combine schemas of array items into one items schema
+    /// Types of the observed values are used to order `anyOf` branches
+    fn join_items(&self, mut items: Vec<Value>, observed: &[&str], location: &Location) -> Value {
+        // Before numbers are unified and unions are collapsed or replaced by the fallback
+        self.record_conflict(location, &items);
+        self.unify_numbers(&mut items);
+        self.unify_formats(&mut items);
+        self.merge_value_keywords(&mut items);
+        self.merge_arrays(&mut items, location);
+        if self.config.discriminate {
+            self.merge_tagged_unions(&mut items, location);
+        }
+        self.merge_object_schemas(&mut items, location);
+        if items.is_empty() {
+            if let Some(merged) = self.merge_objects(&[], location) {
+                return merged;
+            }
+        }
+        if let Fallback::Permissive(max_branches) = self.config.array_items_fallback {
+            if items.len() > max_branches {
+                return json!({});
+            }
+        }
+        if items.len() == 1 {
+            items.swap_remove(0)
+        } else {
+            self.union(items, observed)
+        }
+    }
+
+    /// Merge object schemas among schemas of other types, e.g. `null`, if the merge strategy
+    /// allows it. Properties that are absent in some objects become optional, while explicit
+    /// `null` values are described by the property schemas
+    fn merge_object_schemas(&self, schemas: &mut Vec<Value>, location: &Location) {
+        let is_object = |schema: &Value| {
+            schema["type"] == "object"
+                && (schema["properties"].is_object() || is_open_object(schema))
+        };
+        let first = match schemas.iter().position(is_object) {
+            Some(first) if schemas.iter().filter(|schema| is_object(schema)).count() > 1 => first,
+            _ => return,
+        };
+        let objects: Vec<&Value> = schemas.iter().filter(|schema| is_object(schema)).collect();
+        if let Some(merged) = self.merge_objects(&objects, location) {
+            let mut others: Vec<Value> = schemas
+                .drain(..)
+                .filter(|schema| !is_object(schema))
+                .collect();
+            others.insert(first, merged);
+            *schemas = others;
+        }
+    }
+
+    /// Merge array schemas into one whose items describe the items of all of them,
+    /// instead of keeping a separate array schema per items schema
+    fn merge_arrays(&self, schemas: &mut Vec<Value>, location: &Location) {
+        let is_array = |schema: &Value| {
+            schema.as_object().is_some_and(|map| {
+                map.get("type").and_then(Value::as_str) == Some("array")
+                    && map.get("items").is_some_and(Value::is_object)
+                    && map.keys().all(|keyword| {
+                        [
+                            "type",
+                            "items",
+                            "uniqueItems",
+                            "minItems",
+                            "maxItems",
+                            "x-binary",
+                        ]
+                        .contains(&keyword.as_str())
+                    })
+            })
+        };
+        let first = match schemas.iter().position(is_array) {
+            Some(first) if schemas.iter().filter(|schema| is_array(schema)).count() > 1 => first,
+            _ => return,
+        };
+        let mut items = vec![];
+        let mut others = Vec::with_capacity(schemas.len());
+        let mut unique = true;
+        let mut binary = true;
+        let mut length = Some((u64::MAX, 0));
+        for mut schema in schemas.drain(..) {
+            if !is_array(&schema) {
+                others.push(schema);
+                continue;
+            }
+            unique &= schema.get("uniqueItems") == Some(&Value::Bool(true));
+            binary &= schema.get("x-binary") == Some(&Value::Bool(true));
+            length = join_bounds(length, take_bounds(&mut schema, "minItems", "maxItems"));
+            let mut schema_items = schema["items"].take();
+            let is_union = schema_items.as_object().is_some_and(|map| {
+                map.keys()
+                    .all(|keyword| keyword == "anyOf" || keyword == "$comment")
+            });
+            match schema_items.get_mut("anyOf").map(Value::take) {
+                Some(Value::Array(branches)) if is_union => extend_unique(&mut items, branches),
+                _ => extend_unique(&mut items, vec![schema_items]),
+            }
+        }
+        if !binary {
+            // Byte ranges are only added to items of byte arrays
+            for item in items.iter_mut() {
+                if *item == json!({"type": "integer", "minimum": 0, "maximum": 255}) {
+                    *item = json!({"type": "integer"});
+                }
+            }
+            dedup(&mut items);
+        }
+        let observed = schema_types(&items);
+        let items = self.join_items(items, &observed, &Location::Items(location));
+        let mut merged = json!({"type": "array", "items": items});
+        if unique {
+            merged["uniqueItems"] = Value::Bool(true);
+        }
+        if binary {
+            merged["x-binary"] = Value::Bool(true);
+        }
+        if let Some((min_items, max_items)) = length {
+            merged["minItems"] = json!(min_items);
+            merged["maxItems"] = json!(max_items);
+        }
+        others.insert(first, merged);
+        *schemas = others;
+    }
+
+    /// Infer the items schema of an array of scalars of the same type without inferring each item,
+    /// if nothing depends on their values or locations
+    fn infer_homogeneous_items(&self, array: &[Value]) -> Option<Value> {
+        if !self.shortcuts()
+            || !self.hooks.is_empty()
+            || !self.config.ignore_paths.is_empty()
+            || !self.config.forbidden_types.is_empty()
+            || self.config.examples > 0
+            || self.config.enum_threshold.is_some()
+            || self.config.detect_bool_const
+            || self.config.array_items_fallback == Fallback::Permissive(0)
+        {
+            return None;
+        }
+        let first = array.first()?;
+        let type_ = type_name(first);
+        match first {
+            Value::Null | Value::Bool(_) => {}
+            Value::Number(_) if !self.config.number_bounds => {}
+            Value::String(_)
+                if !self.config.detect_format
+                    && !self.config.string_length_bounds
+                    && !self.config.detect_embedded_json => {}
+            _ => return None,
+        }
+        if array.iter().all(|item| type_name(item) == type_) {
+            Some(json!({ "type": type_ }))
+        } else {
+            None
+        }
+    }
+
+    /// Infer `oneOf` of per-tag schemas for an array of tagged objects, if there is a tag
+    fn infer_tagged_union(
+        &self,
+        array: &[Value],
+        location: &Location,
+    ) -> Result<Option<Value>, InferError> {
+        let objects: Option<Vec<&Map<String, Value>>> =
+            array.iter().map(Value::as_object).collect();
+        let (key, variants) = match objects
+            .and_then(|objects| find_discriminator(&objects, self.config.discriminator.as_deref()))
+        {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        let mut schemas = Vec::with_capacity(variants.len());
+        for (tag, indices) in variants {
+            let mut items = Vec::with_capacity(indices.len());
+            for idx in indices {
+                items.push(self._infer(&array[idx], &Location::Index(location, idx))?);
+            }
+            schemas.push(self.join_variant(items, key, tag, &Location::Items(location)));
+        }
+        Ok(Some(self.tagged_union(schemas, key)))
+    }
+
+    /// Combine schemas of objects with the same tag value into the schema of their variant
+    fn join_variant(
+        &self,
+        mut items: Vec<Value>,
+        key: &str,
+        tag: &str,
+        location: &Location,
+    ) -> Value {
+        dedup(&mut items);
+        let mut schema = if items.len() == 1 {
+            items.swap_remove(0)
+        } else if let Some(merged) =
+            self.merge_objects(&items.iter().collect::<Vec<&Value>>(), location)
+        {
+            merged
+        } else {
+            let observed = schema_types(&items);
+            self.union(items, &observed)
+        };
+        // Every object of the variant has the tag, so it is required after merging too
+        let tag = if self.config.draft == Draft::Draft4 {
+            json!({"type": "string", "enum": [tag]})
+        } else {
+            json!({"type": "string", "const": tag})
+        };
+        if let Some(properties) = schema.get_mut("properties") {
+            properties[key] = tag;
+        }
+        schema
+    }
+
+    fn tagged_union(&self, variants: Vec<Value>, key: &str) -> Value {
+        let note = format!("tagged union of {} variants by '{}'", variants.len(), key);
+        let mut union = json!({ "oneOf": variants });
+        self.annotate(&mut union, note);
+        union
+    }
+
+    /// Merge tagged unions of items of different arrays, e.g. of the same property in different
+    /// objects, into one `oneOf` with a schema per tag value, instead of nesting them in `anyOf`
+    fn merge_tagged_unions(&self, schemas: &mut Vec<Value>, location: &Location) {
+        let is_union = |schema: &Value| {
+            schema.as_object().is_some_and(|map| {
+                map.keys()
+                    .all(|keyword| keyword == "oneOf" || keyword == "$comment")
+                    && map.get("oneOf").is_some_and(Value::is_array)
+            })
+        };
+        let key = match self.config.discriminator.as_deref() {
+            Some(key) => key.to_owned(),
+            None => match schemas
+                .iter()
+                .find(|schema| is_union(schema))
+                .and_then(|schema| find_tag_key(&schema["oneOf"]))
+            {
+                Some(key) => key.to_owned(),
+                None => return,
+            },
+        };
+        let is_tagged = |schema: &Value| {
+            is_union(schema)
+                && schema["oneOf"].as_array().is_some_and(|branches| {
+                    branches
+                        .iter()
+                        .all(|branch| tag_value(branch, &key).is_some())
+                })
+        };
+        let first = match schemas.iter().position(is_tagged) {
+            Some(first) if schemas.iter().filter(|schema| is_tagged(schema)).count() > 1 => first,
+            _ => return,
+        };
+        let mut variants: Vec<(String, Vec<Value>)> = Vec::new();
+        for schema in schemas.iter().filter(|schema| is_tagged(schema)) {
+            for branch in schema["oneOf"].as_array().into_iter().flatten() {
+                let tag = tag_value(branch, &key).unwrap_or_default();
+                match variants.iter_mut().find(|(known, _)| known == tag) {
+                    Some((_, branches)) => branches.push(branch.clone()),
+                    None => variants.push((tag.to_owned(), vec![branch.clone()])),
+                }
+            }
+        }
+        let variants = variants
+            .into_iter()
+            .map(|(tag, branches)| self.join_variant(branches, &key, &tag, location))
+            .collect();
+        let merged = self.tagged_union(variants, &key);
+        let mut others: Vec<Value> = schemas
+            .drain(..)
+            .filter(|schema| !is_tagged(schema))
+            .collect();
+        others.insert(first, merged);
+        *schemas = others;
+    }
+
+    /// Join schemas via `anyOf`, ordered according to `union_order` and observed value types
+    fn union(&self, mut schemas: Vec<Value>, observed: &[&str]) -> Value {
+        if let Some(max_branches) = self.config.max_union_branches {
+            if schemas.len() > max_branches {
+                return self.collapse_union(&schemas);
+            }
+        }
+        if let Some(order) = self.config.union_order {
+            order_branches(&mut schemas, observed, order);
+        }
+        let note = format!("union of {} schemas", schemas.len());
+        let mut union = json!({ "anyOf": schemas });
+        self.annotate(&mut union, note);
+        union
+    }
+
+    /// Describe schemas of too many branches only by their types, or by `{}` if some of them
+    /// have no single type
+    fn collapse_union(&self, schemas: &[Value]) -> Value {
+        let mut types: Vec<&Value> = vec![];
+        for schema in schemas {
+            match schema.get("type") {
+                Some(type_ @ Value::String(_)) => {
+                    if !types.contains(&type_) {
+                        types.push(type_)
+                    }
+                }
+                _ => return json!({}),
+            }
+        }
+        let mut collapsed = if types.len() == 1 {
+            json!({ "type": types[0] })
+        } else {
+            json!({ "type": types })
+        };
+        self.annotate(
+            &mut collapsed,
+            format!(
+                "union of {} schemas collapsed to their types",
+                schemas.len()
+            ),
+        );
+        collapsed
+    }
+
+    /// Describe how the schema was derived in `$comment`, if `annotate_provenance` is set
+    fn annotate(&self, schema: &mut Value, note: String) {
+        if self.config.annotate_provenance {
+            schema["$comment"] = Value::String(note);
+        }
+    }
+
+    /// Infer schemas of distinct array items with their hashes, in the order of the items
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    fn infer_items(
+        &self,
+        array: &[Value],
+        location: &Location,
+    ) -> Result<Vec<(u64, Value)>, InferError> {
+        let items = self.distinct_items(array);
+        if items.len() > self.config.parallel_threshold {
+            items
+                .par_iter()
+                .map(|&(idx, item)| self.infer_hashed(item, &Location::Index(location, idx)))
+                .collect()
+        } else {
+            self.infer_items_serial(&items, location)
+        }
+    }
+
+    /// Infer schemas of distinct array items with their hashes, in the order of the items.
+    /// Without the `parallel` feature or on WASM, where there are no threads,
+    /// items are always processed sequentially
+    #[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+    fn infer_items(
+        &self,
+        array: &[Value],
+        location: &Location,
+    ) -> Result<Vec<(u64, Value)>, InferError> {
+        self.infer_items_serial(&self.distinct_items(array), location)
+    }
+
+    fn infer_items_serial(
+        &self,
+        items: &[(usize, &Value)],
+        location: &Location,
+    ) -> Result<Vec<(u64, Value)>, InferError> {
+        items
+            .iter()
+            .map(|&(idx, item)| self.infer_hashed(item, &Location::Index(location, idx)))
+            .collect()
+    }
+
+    /// Array items with their indices, possibly sampled, without repeated values.
+    /// Identical values have identical schemas, so it is enough to infer only the first one.
+    /// Hooks and ignored paths may depend on the location of a value, so with them all items
+    /// are kept
+    fn distinct_items<'v>(&self, array: &'v [Value]) -> Vec<(usize, &'v Value)> {
+        let sampled = self.sample_items(array);
+        if !self.shortcuts() || !self.hooks.is_empty() || !self.config.ignore_paths.is_empty() {
+            return sampled;
+        }
+        let mut seen: BTreeMap<u64, Vec<&Value>> = BTreeMap::new();
+        let mut items = Vec::new();
+        for (idx, item) in sampled {
+            let known = seen.entry(canonical_hash(item)).or_default();
+            // Hashes may collide, therefore values are compared too
+            if !known.contains(&item) {
+                known.push(item);
+                items.push((idx, item));
+            }
+        }
+        items
+    }
+
+    /// Array items with their indices, or a sample of them if the array is larger than
+    /// `sample_size`
+    fn sample_items<'v>(&self, array: &'v [Value]) -> Vec<(usize, &'v Value)> {
+        match self.config.sample_size {
+            Some(size) if size > 0 && array.len() > size => {
+                sample_indices(array.len(), size, &mut Rng::new(self.config.seed))
+                    .into_iter()
+                    .map(|idx| (idx, &array[idx]))
+                    .collect()
+            }
+            _ => array.iter().enumerate().collect(),
+        }
+    }
+
+    fn infer_hashed(&self, item: &Value, location: &Location) -> Result<(u64, Value), InferError> {
+        let mut inferred = self._infer(item, location)?;
+        // Scalar items, e.g. of categorical arrays, may be described by `enum` too
+        if !item.is_array() && !item.is_object() && !is_empty_schema(&inferred) {
+            self.annotate_enum(&mut inferred, item);
+        }
+        Ok((canonical_hash(&inferred), inferred))
+    }
+
+    /// Infer schema for JSON object
+    fn infer_object(
+        &self,
+        object: &Map<String, Value>,
+        location: &Location,
+    ) -> Result<Value, InferError> {
+        if self.config.open_empty_objects && object.is_empty() {
+            return Ok(json!({"type": "object"}));
+        }
+        let mut properties = BTreeMap::new();
+        let mut required = Vec::with_capacity(object.len());
+        for (key, value) in object.iter() {
+            required.push(key);
+            properties.insert(key, self._infer(value, &Location::Key(location, key))?);
+        }
+        if let Some(map) = self.infer_map(object, &properties, location) {
+            return Ok(map);
+        }
+        for (key, schema) in properties.iter_mut() {
+            let value = &object[key.as_str()];
+            // Ignored values have an empty schema that shouldn't get any keywords
+            if !value.is_array() && !value.is_object() && !is_empty_schema(schema) {
+                self.annotate_scalar_property(schema, value);
+            }
+            if let Some(predicate) = &self.write_only {
+                if self.config.draft >= Draft::Draft7 && !is_empty_schema(schema) && predicate(key)
+                {
+                    schema["writeOnly"] = Value::Bool(true);
+                }
+            }
+        }
+        let mut data = json!({"type": "object", "properties": properties});
+        let too_deep = self
+            .config
+            .required_max_depth
+            .is_some_and(|max_depth| location.depth() > max_depth);
+        // Annotations are derived from `required`, it is removed later if not needed
+        if !too_deep && (self.config.emit_required || self.config.optional_annotation) {
+            data["required"] = json!(required);
+        }
+        if self.config.emit_property_order {
+            data["x-property-order"] = json!(required);
+        }
+        if self.config.emit_property_names {
+            if let Some(names) = infer_key_style(object.keys()) {
+                data["propertyNames"] = names;
+            }
+        }
+        Ok(data)
+    }
+
+    /// Add value-based keywords to schemas of scalar properties
+    fn annotate_scalar_property(&self, schema: &mut Value, value: &Value) {
+        if self.config.detect_defaults {
+            schema["default"] = value.clone();
+        }
+        if self.config.detect_bool_const && value.is_boolean() {
+            if self.config.draft == Draft::Draft4 {
+                schema["enum"] = json!([value]);
+            } else {
+                schema["const"] = value.clone();
+            }
+        }
+        if self.config.assume_unsigned && value.as_u64().is_some() {
+            schema["minimum"] = json!(0);
+        }
+        if self.config.assume_positive && value.as_f64().is_some_and(|number| number > 0.0) {
+            self.exclusive_minimum(schema, json!(0));
+        }
+        self.annotate_enum(schema, value);
+    }
+
+    /// Add `enum` with the value if `enum_threshold` is set and the schema type is eligible.
+    /// Values of schemas that are merged later are joined, unless there are too many of them
+    fn annotate_enum(&self, schema: &mut Value, value: &Value) {
+        if matches!(self.config.enum_threshold, Some(threshold) if threshold > 0)
+            && self
+                .config
+                .enum_types
+                .iter()
+                .any(|enum_type| schema["type"] == enum_type.as_str())
+        {
+            schema["enum"] = json!([value]);
+        }
+    }
+
+    /// Infer schema for a map-like object, if map detection is enabled and the object qualifies
+    fn infer_map(
+        &self,
+        object: &Map<String, Value>,
+        properties: &BTreeMap<&String, Value>,
+        location: &Location,
+    ) -> Option<Value> {
+        let threshold = self.config.map_threshold?;
+        if object.is_empty() || object.len() < threshold {
+            return None;
+        }
+        let mut schemas: Vec<Value> = properties.values().cloned().collect();
+        dedup(&mut schemas);
+        self.merge_value_keywords(&mut schemas);
+        let values = if schemas.len() == 1 {
+            schemas.swap_remove(0)
+        } else {
+            self.merge_objects(
+                &schemas.iter().collect::<Vec<&Value>>(),
+                &Location::Items(location),
+            )?
+        };
+        let mut data = json!({"type": "object", "additionalProperties": values});
+        self.annotate(&mut data, format!("map of {} properties", object.len()));
+        if self
+            .config
+            .key_enum_threshold
+            .is_some_and(|threshold| object.len() <= threshold)
+        {
+            data["propertyNames"] = json!({ "enum": object.keys().collect::<Vec<_>>() });
+        } else if let Some(pattern) = infer_key_pattern(object.keys()) {
+            data["propertyNames"] = json!({ "pattern": pattern });
+        }
+        Some(data)
+    }
+
+    /// Merge object schemas if the merge strategy allows it
+    fn merge_objects(&self, schemas: &[&Value], location: &Location) -> Option<Value> {
+        match self.config.object_merge_strategy {
+            ObjectMergeStrategy::Merge if self.config.case_insensitive_keys => {
+                let schemas = unify_key_case(schemas);
+                self.try_merge(&schemas.iter().collect::<Vec<&Value>>(), location)
+            }
+            ObjectMergeStrategy::Merge => self.try_merge(schemas, location),
+            ObjectMergeStrategy::Compose => None,
+        }
+    }
+
+    /// Try to merge multiple object schemas into one
+    fn try_merge(&self, data: &[&Value], location: &Location) -> Option<Value> {
+        if data.iter().all(|item| {
+            item["type"] == "object" && (item["properties"].is_object() || is_open_object(item))
+        }) {
+            let mut properties_types: BTreeMap<String, Vec<&Value>> = BTreeMap::new();
+            // Types of properties in all the merged schemas, including repeated ones
+            let mut observed: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+            let mut known_required: Vec<Vec<&str>> = vec![];
+            let mut property_order: Vec<&Value> = vec![];
+            let mut new = json!({"type": "object"});
+            for item in data.iter() {
+                if let Some(order) = item.get("x-property-order").and_then(Value::as_array) {
+                    for key in order {
+                        if !property_order.contains(&key) {
+                            property_order.push(key)
+                        }
+                    }
+                }
+                let properties = item.get("properties").and_then(Value::as_object);
+                for (name, schema) in properties.into_iter().flatten() {
+                    if self.config.union_order.is_some() {
+                        let types = observed.entry(name.as_str()).or_default();
+                        types.push(schema["type"].as_str().unwrap_or_default());
+                    }
+                    let known_types = properties_types.entry(name.clone()).or_default();
+                    if !known_types.contains(&schema) {
+                        known_types.push(schema)
+                    }
+                }
+                collect_required(&mut known_required, item);
+            }
+            let map = new.as_object_mut().unwrap();
+            // Keywords set by hooks or other sources are kept if all the schemas agree on them
+            if let Some(Value::Object(first)) = data.first() {
+                for (keyword, value) in first {
+                    if !MERGED_KEYWORDS.contains(&keyword.as_str())
+                        && data.iter().all(|item| item.get(keyword) == Some(value))
+                    {
+                        map.insert(keyword.clone(), value.clone());
+                    }
+                }
+            }
+            fill_required(map, known_required);
+            self.fill_properties(map, &properties_types, &observed, location);
+            if self.config.emit_property_names {
+                // Describe all the merged keys, not only the keys of the first object
+                map.remove("propertyNames");
+                let style = match map.get("properties") {
+                    Some(Value::Object(properties)) => infer_key_style(properties.keys()),
+                    _ => None,
+                };
+                if let Some(names) = style {
+                    if !data.iter().any(|item| is_open_object(item)) {
+                        map.insert("propertyNames".into(), names);
+                    }
+                }
+            }
+            // Open objects accept any properties already
+            if data.len() > 1
+                && !map.contains_key("additionalProperties")
+                && !data.iter().any(|item| is_open_object(item))
+            {
+                match &self.config.merged_additional_properties {
+                    AdditionalProperties::Allow => {}
+                    AdditionalProperties::Deny => {
+                        map.insert("additionalProperties".into(), Value::Bool(false));
+                    }
+                    AdditionalProperties::Schema(schema) => {
+                        map.insert("additionalProperties".into(), schema.clone());
+                    }
+                }
+            }
+            if !property_order.is_empty() {
+                map.insert("x-property-order".into(), json!(property_order));
+            }
+            self.annotate(
+                &mut new,
+                format!("merged from {} object schemas", data.len()),
+            );
+            return Some(new);
+        }
+        None
+    }
+
+    /// Fill "properties" with collected values.
+    /// Each property can be either of one type or multiple types joined via "anyOf"
+    fn fill_properties(
+        &self,
+        map: &mut Map<String, Value>,
+        properties_types: &BTreeMap<String, Vec<&Value>>,
+        observed: &BTreeMap<&str, Vec<&str>>,
+        location: &Location,
+    ) {
+        let properties = map
+            .entry("properties")
+            .or_insert(json!({}))
+            .as_object_mut()
+            .unwrap();
+        for (property, known_types) in properties_types.iter() {
+            let location = Location::Key(location, property);
+            self.record_conflict(&location, known_types.iter().copied());
+            let mut known_types: Vec<Value> = known_types.iter().map(|&x| x.clone()).collect();
+            self.reconcile_defaults(&mut known_types);
+            self.reconcile_bool_const(&mut known_types);
+            self.reconcile_bounds(&mut known_types);
+            self.unify_numbers(&mut known_types);
+            self.unify_formats(&mut known_types);
+            self.merge_value_keywords(&mut known_types);
+            self.merge_arrays(&mut known_types, &location);
+            self.merge_object_schemas(&mut known_types, &location);
+            let types = if known_types.len() == 1 {
+                known_types.swap_remove(0)
+            } else {
+                let observed = observed
+                    .get(property.as_str())
+                    .map_or(&[][..], Vec::as_slice);
+                self.union(known_types, observed)
+            };
+            properties.insert(property.clone(), types);
+        }
+    }
+
+    /// Merge integer schemas into number ones if both are present.
+    /// Every integer is a number, therefore a bare `integer` schema, i.e. without keywords other
+    /// than annotations, is redundant next to a bare `number` one. Other integer schemas are merged only if `unify_int_float` is set
+    fn unify_numbers(&self, schemas: &mut Vec<Value>) {
+        if self.config.unify_int_float && schemas.iter().any(|schema| schema["type"] == "number") {
+            for schema in schemas.iter_mut() {
+                if schema["type"] == "integer" {
+                    schema["type"] = Value::String("number".into());
+                }
+            }
+            dedup(schemas);
+        } else if schemas.iter().any(|schema| is_bare(schema, "number")) {
+            for schema in schemas.iter_mut() {
+                if is_bare(schema, "integer") {
+                    schema["type"] = Value::String("number".into());
+                }
+            }
+            dedup(schemas);
+        }
+    }
+
+    /// Join `enum`, `examples` and length bounds of schemas that are otherwise the same.
+    /// If there are more distinct values than `enum_threshold` allows, the enum is dropped
+    fn merge_value_keywords(&self, schemas: &mut Vec<Value>) {
+        if schemas.len() < 2
+            || (self.config.enum_threshold.is_none()
+                && self.config.examples == 0
+                && !self.config.string_length_bounds
+                && !self.config.number_bounds
+                && !self.config.array_length_bounds)
+        {
+            return;
+        }
+        let mut merged: Vec<(Value, ValueKeywords)> = Vec::with_capacity(schemas.len());
+        for mut schema in schemas.drain(..) {
+            let keywords = ValueKeywords::take(
+                &mut schema,
+                self.config.string_length_bounds,
+                self.config.number_bounds,
+                self.config.array_length_bounds,
+            );
+            if let Some((_, known)) = merged.iter_mut().find(|(other, _)| *other == schema) {
+                known.join(keywords)
+            } else {
+                merged.push((schema, keywords))
+            }
+        }
+        for (mut schema, keywords) in merged {
+            if let Some(mut values) = keywords.values {
+                if self
+                    .config
+                    .enum_threshold
+                    .is_some_and(|threshold| values.len() <= threshold)
+                {
+                    values.sort_by(compare_scalars);
+                    schema["enum"] = Value::Array(values);
+                }
+            }
+            if !keywords.examples.is_empty() {
+                let mut examples = keywords.examples;
+                examples.truncate(self.config.examples);
+                schema["examples"] = Value::Array(examples);
+            }
+            if let Some((min_length, max_length)) = keywords.length {
+                schema["minLength"] = json!(min_length);
+                schema["maxLength"] = json!(max_length);
+            }
+            if let Some((minimum, maximum)) = keywords.range {
+                schema["minimum"] = minimum;
+                schema["maximum"] = maximum;
+            }
+            if let Some((min_items, max_items)) = keywords.items {
+                schema["minItems"] = json!(min_items);
+                schema["maxItems"] = json!(max_items);
+            }
+            schemas.push(schema)
+        }
+    }
+
+    /// Remove `format` from schemas of a type if they disagree on it, if `simplify_unions` is set.
+    /// With `FormatMerge::Union`, formats of strings that all have some format are kept as `anyOf`.
+    /// Empty strings match no format, so they take the format of the other strings
+    fn unify_formats(&self, schemas: &mut Vec<Value>) {
+        let empty: Vec<bool> = schemas
+            .iter_mut()
+            .map(|schema| {
+                schema
+                    .as_object_mut()
+                    .and_then(|map| map.remove(EMPTY_STRING_MARKER))
+                    .is_some()
+            })
+            .collect();
+        if !self.config.simplify_unions || schemas.len() < 2 {
+            return;
+        }
+        let mut types = vec![];
+        for schema in schemas.iter() {
+            if schema["type"].is_string() && !types.contains(&schema["type"]) {
+                types.push(schema["type"].clone());
+            }
+        }
+        for type_ in &types {
+            let mut formats = vec![];
+            let mut all_formatted = true;
+            let mut has_empty = false;
+            for (schema, &empty) in schemas.iter().zip(&empty) {
+                if schema["type"] != *type_ {
+                    continue;
+                }
+                if empty {
+                    has_empty = true;
+                    continue;
+                }
+                match schema.get("format") {
+                    Some(format) => {
+                        let format = json!({ "format": format });
+                        if !formats.contains(&format) {
+                            formats.push(format);
+                        }
+                    }
+                    None => all_formatted = false,
+                }
+            }
+            let agreed = all_formatted && formats.len() == 1;
+            if formats.is_empty() || (agreed && !has_empty) {
+                continue;
+            }
+            for schema in schemas.iter_mut().filter(|schema| schema["type"] == *type_) {
+                if agreed {
+                    schema["format"] = formats[0]["format"].clone();
+                } else if let Some(map) = schema.as_object_mut() {
+                    map.remove("format");
+                    if self.config.format_merge == FormatMerge::Union && all_formatted {
+                        map.insert("anyOf".into(), Value::Array(formats.clone()));
+                    }
+                }
+            }
+        }
+        dedup(schemas);
+    }
+
+    /// Keep bounds only if all the schemas of the same numeric type agree on them
+    fn reconcile_bounds(&self, schemas: &mut Vec<Value>) {
+        if !self.config.assume_unsigned && !self.config.assume_positive {
+            return;
+        }
+        for numeric_type in &["integer", "number"] {
+            for keyword in BOUND_KEYWORDS {
+                // Observed bounds are widened when merged
+                if *keyword == "minimum" && self.config.number_bounds {
+                    continue;
+                }
+                let mut numbers = schemas
+                    .iter()
+                    .filter(|schema| schema["type"] == *numeric_type)
+                    .map(|schema| schema.get(keyword));
+                let first = numbers.next().flatten();
+                if first.is_some() && numbers.all(|bound| bound == first) {
+                    continue;
+                }
+                for schema in schemas.iter_mut() {
+                    if let Value::Object(map) = schema {
+                        if map.get("type") == Some(&json!(numeric_type)) {
+                            map.remove(*keyword);
+                        }
+                    }
+                }
+            }
+        }
+        dedup(schemas);
+    }
+
+    /// Set an exclusive lower bound in the form appropriate for the draft
+    fn exclusive_minimum(&self, schema: &mut Value, bound: Value) {
+        if self.config.draft == Draft::Draft4 {
+            schema["minimum"] = bound;
+            schema["exclusiveMinimum"] = Value::Bool(true);
+        } else {
+            schema["exclusiveMinimum"] = bound;
+        }
+    }
+
+    /// Keep the value of boolean schemas only if all of them agree on it
+    fn reconcile_bool_const(&self, schemas: &mut Vec<Value>) {
+        if !self.config.detect_bool_const {
+            return;
+        }
+        let keyword = if self.config.draft == Draft::Draft4 {
+            "enum"
+        } else {
+            "const"
+        };
+        let mut values = schemas
+            .iter()
+            .filter(|schema| schema["type"] == "boolean")
+            .map(|schema| schema.get(keyword));
+        let first = values.next().flatten();
+        if values.all(|value| value == first) {
+            return;
+        }
+        for schema in schemas.iter_mut() {
+            if let Value::Object(map) = schema {
+                if map.get("type") == Some(&json!("boolean")) {
+                    map.remove(keyword);
+                }
+            }
+        }
+        dedup(schemas);
+    }
+
+    /// Keep `default` only if all the schemas of a property agree on it
+    fn reconcile_defaults(&self, schemas: &mut Vec<Value>) {
+        if self.config.detect_defaults && schemas.len() > 1 {
+            let first = schemas[0].get("default").cloned();
+            if first.is_none()
+                || schemas
+                    .iter()
+                    .any(|schema| schema.get("default") != first.as_ref())
+            {
+                for schema in schemas.iter_mut() {
+                    if let Value::Object(map) = schema {
+                        map.remove("default");
+                    }
+                }
+                dedup(schemas);
+            }
+        }
+    }
+}
+
+/// Shortcut for inference with default settings
+pub fn infer(input: &Value) -> Value {
+    JSONSchema::new(input).infer()
+}
+
+/// Shortcut for fallible inference with default settings
+pub fn try_infer(input: &Value) -> Result<Value, InferError> {
+    JSONSchema::new(input).try_infer()
+}
+
+/// Refine an existing schema with new samples, using default settings
+pub fn refine(existing: &Value, samples: &[Value]) -> Value {
+    samples.iter().fold(existing.clone(), |schema, sample| {
+        JSONSchema::new(sample).refine(&schema)
+    })
+}
+
+/// Infer a schema describing every sample, as if they were items of one array.
+/// Samples of different types, e.g. objects and arrays, are described by `anyOf` at the root
+pub fn infer_samples(samples: &[Value]) -> Value {
+    if samples.is_empty() {
+        return SchemaBuilder::new().finish();
+    }
+    let inferrer = JSONSchema::new(&Value::Null);
+    let (mut schema, _) = inferrer
+        .recorded(false, || inferrer.infer_array(samples, &Location::Root))
+        .expect("Inference failed");
+    let mut schema = schema["items"].take();
+    remove_empty_string_markers(&mut schema);
+    set_schema_uri(&mut schema, Draft::Draft7.uri().into());
+    schema
+}
+
+/// Infer a schema describing every sample, like `infer_samples`, with up to `limit` distinct
+/// samples in `examples` of the root schema
+pub fn infer_samples_with_examples(samples: &[Value], limit: usize) -> Value {
+    let mut schema = infer_samples(samples);
+    let mut examples: Vec<&Value> = Vec::with_capacity(limit.min(samples.len()));
+    for sample in samples {
+        if examples.len() == limit {
+            break;
+        }
+        if !examples.contains(&sample) {
+            examples.push(sample);
+        }
+    }
+    if !examples.is_empty() {
+        schema["examples"] = json!(examples);
+    }
+    schema
+}
+
+/// Infer a schema describing every sample, consuming them one by one, with the same result as
+/// `infer_samples`. Unlike it, it keeps only distinct schemas of the samples in memory rather
+/// than the samples themselves
+pub fn infer_from_iter(samples: impl IntoIterator<Item = Value>) -> Value {
+    let inferrer = JSONSchema::new(&Value::Null);
+    let (schema, _) = inferrer
+        .recorded(false, || {
+            // Keyed by hashes of sample schemas, as in `JSONSchema::infer_items`
+            let mut schemas = BTreeMap::new();
+            for (idx, sample) in samples.into_iter().enumerate() {
+                let (hash, schema) =
+                    inferrer.infer_hashed(&sample, &Location::Index(&Location::Root, idx))?;
+                insert_distinct(&mut schemas, hash, schema);
+            }
+            if schemas.is_empty() {
+                return Ok(None);
+            }
+            let location = Location::Items(&Location::Root);
+            let schemas = schemas.into_values().flatten().collect();
+            Ok(Some(inferrer.join_items(schemas, &[], &location)))
+        })
+        .expect("Inference failed");
+    let mut schema = match schema {
+        Some(schema) => schema,
+        None => return SchemaBuilder::new().finish(),
+    };
+    remove_empty_string_markers(&mut schema);
+    set_schema_uri(&mut schema, Draft::Draft7.uri().into());
+    schema
+}
+
+/// Infer a schema for JSON Lines, where each non-empty line is a separate sample.
+/// Lines of different shapes are kept as separate `anyOf` branches instead of being merged,
+/// and tagged objects, e.g. events with a `type` property, are described as `oneOf` per tag
+///
+/// ```rust
+/// use infers_jsonschema::infer_ndjson_union;
+/// use serde_json::json;
+///
+/// let input = "{\"id\": 1}\n[1, 2]\n";
+/// assert_eq!(
+///     infer_ndjson_union(input.as_bytes()).expect("Invalid input"),
+///     json!({
+///         "anyOf": [
+///             {"type": "object", "properties": {"id": {"type": "integer"}}, "required": ["id"]},
+///             {"type": "array", "items": {"type": "integer"}}
+///         ],
+///         "$schema": "http://json-schema.org/draft-07/schema#"
+///     })
+/// );
+/// ```
+#[cfg(feature = "std")]
+pub fn infer_ndjson_union(reader: impl std::io::BufRead) -> Result<Value, InferError> {
+    let mut samples = vec![];
+    for (number, line) in reader.lines().enumerate() {
+        let line = line.map_err(|error| InferError::InvalidInput {
+            message: error.to_string(),
+        })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let sample = serde_json::from_str(&line).map_err(|error| InferError::InvalidInput {
+            message: format!("line {}: {}", number + 1, error),
+        })?;
+        samples.push(sample);
+    }
+    if samples.is_empty() {
+        return Ok(SchemaBuilder::new().finish());
+    }
+    let inferrer = JSONSchema::new(&Value::Null)
+        .discriminator(None)
+        .object_merge_strategy(ObjectMergeStrategy::Compose);
+    let (mut schema, _) =
+        inferrer.recorded(false, || inferrer.infer_array(&samples, &Location::Root))?;
+    let mut schema = schema["items"].take();
+    remove_empty_string_markers(&mut schema);
+    set_schema_uri(&mut schema, Draft::Draft7.uri().into());
+    Ok(schema)
+}
+
+/// Infer a schema for each top-level property of an object with default settings, keyed by
+/// property names. They are the same as `properties` of the schema given by `infer`.
+/// Inputs other than objects have no properties
+///
+/// ```rust
+/// use infers_jsonschema::infer_properties;
+/// use serde_json::json;
+///
+/// let properties = infer_properties(&json!({"id": 1, "tags": ["a"]}));
+/// assert_eq!(properties["id"], json!({"type": "integer"}));
+/// assert_eq!(properties["tags"], json!({"type": "array", "items": {"type": "string"}}));
+/// ```
+///
+/// # Panics
+///
+/// Panics if the input can't be described by a schema. See `try_infer` for details.
+pub fn infer_properties(input: &Value) -> BTreeMap<String, Value> {
+    let inferrer = JSONSchema::new(input);
+    let object = match input {
+        Value::Object(object) => object,
+        _ => return BTreeMap::new(),
+    };
+    object
+        .iter()
+        .map(|(key, value)| {
+            let (mut schema, _) = inferrer
+                .recorded(false, || {
+                    inferrer._infer(value, &Location::Key(&Location::Root, key))
+                })
+                .expect("Inference failed");
+            inferrer.finalize(&mut schema);
+            (key.clone(), schema)
+        })
+        .collect()
+}
+
+/// The key of objects annotated by `annotate_in_place` that holds types of their properties
+pub const TYPES_KEY: &str = "$types";
+
+/// Annotate the input in place instead of inferring a separate schema, using default settings.
+///
+/// **The input is mutated**: every object in it gets a `TYPES_KEY` property mapping names of its
+/// other properties to schemas of their types, e.g. `{"type": "string", "format": "date"}`.
+/// Properties that are objects or arrays are described only by their types, as objects inside
+/// them have annotations of their own. An existing `TYPES_KEY` property is replaced.
+///
+/// ```rust
+/// use infers_jsonschema::annotate_in_place;
+/// use serde_json::json;
+///
+/// let mut data = json!({"id": 1, "tags": ["a"]});
+/// annotate_in_place(&mut data);
+/// assert_eq!(
+///     data,
+///     json!({
+///         "id": 1,
+///         "tags": ["a"],
+///         "$types": {"id": {"type": "integer"}, "tags": {"type": "array"}}
+///     })
+/// );
+/// ```
+///
+/// # Panics
+///
+/// Panics if the input can't be described by a schema. See `try_infer` for details.
+pub fn annotate_in_place(input: &mut Value) {
+    let inferrer = JSONSchema::new(&Value::Null);
+    annotate_value(&inferrer, input);
+}
+
+fn annotate_value(inferrer: &JSONSchema<'_>, value: &mut Value) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                annotate_value(inferrer, item);
+            }
+        }
+        Value::Object(object) => {
+            object.remove(TYPES_KEY);
+            let mut types = Map::new();
+            for (key, value) in object.iter_mut() {
+                annotate_value(inferrer, value);
+                let schema = match value {
+                    Value::Array(_) => json!({"type": "array"}),
+                    Value::Object(_) => json!({"type": "object"}),
+                    scalar => {
+                        let (mut schema, _) = inferrer
+                            .recorded(false, || inferrer._infer(scalar, &Location::Root))
+                            .expect("Inference failed");
+                        remove_empty_string_markers(&mut schema);
+                        schema
+                    }
+                };
+                types.insert(key.clone(), schema);
+            }
+            object.insert(TYPES_KEY.into(), Value::Object(types));
+        }
+        _ => {}
+    }
+}
+
+/// Shortcut for inference with statistics and default settings
+pub fn infer_with_stats(input: &Value) -> Result<(Value, InferStats), InferError> {
+    JSONSchema::new(input).infer_with_stats()
+}
+
+/// Infer a schema from JSON text and serialize it back to text.
+/// Useful where passing `serde_json::Value` around is inconvenient, e.g. in WASM bindings
+pub fn infer_to_string(input: &str) -> Result<String, serde_json::Error> {
+    let data: Value = serde_json::from_str(input)?;
+    serde_json::to_string(&infer(&data))
+}
+
+/// Merge multiple object schemas into one.
+///
+/// The merged schema contains:
+///   - `properties` with every property seen in any of the schemas. A property that has different
+///     schemas in different inputs is expressed as `anyOf` of them;
+///   - `required` with properties that are required in all the schemas.
+///
+/// Open object schemas, i.e. `{"type": "object"}` without other keywords, accept any properties
+/// and are merged too, so properties of the other schemas are not required.
+/// Returns `None` if there is nothing to merge or if any of the schemas is neither an object
+/// schema with `properties` nor an open object schema.
+///
+/// ```rust
+/// use infers_jsonschema::merge_schemas;
+/// use serde_json::json;
+///
+/// let first = json!({"type": "object", "properties": {"a": {"type": "integer"}}, "required": ["a"]});
+/// let second = json!({"type": "object", "properties": {"b": {"type": "string"}}, "required": ["b"]});
+/// assert_eq!(
+///     merge_schemas(&[&first, &second]),
+///     Some(json!({
+///         "type": "object",
+///         "properties": {"a": {"type": "integer"}, "b": {"type": "string"}}
+///     }))
+/// );
+/// let open = json!({"type": "object"});
+/// assert_eq!(
+///     merge_schemas(&[&open, &first]),
+///     Some(json!({"type": "object", "properties": {"a": {"type": "integer"}}}))
+/// );
+/// assert_eq!(merge_schemas(&[&open, &json!({"type": "string"})]), None);
+/// ```
+pub fn merge_schemas(schemas: &[&Value]) -> Option<Value> {
+    if schemas.is_empty() {
+        return None;
+    }
+    JSONSchema::new(&Value::Null).try_merge(schemas, &Location::Root)
+}
+
+/// `serde_json::Number` can't be non-finite, NaN and infinities become `null` in `Value`.
+/// Decoders of other formats report them with `InferError::NonFiniteNumber` instead
+fn infer_number(number: &Number) -> Value {
+    if is_float(number) {
+        json!({"type": "number"})
+    } else {
+        json!({"type": "integer"})
+    }
+}
+
+#[cfg(not(feature = "arbitrary_precision"))]
+fn is_float(number: &Number) -> bool {
+    number.is_f64()
+}
+
+/// Whether the number is written with a fraction or an exponent, e.g. `5.0`, regardless of its
+/// value. Integers that don't fit into 64 bits are still integers
+#[cfg(feature = "arbitrary_precision")]
+fn is_float(number: &Number) -> bool {
+    number.to_string().contains(['.', 'e', 'E'])
+}
+
+/// Keywords computed by `try_merge` from the corresponding keywords of the merged schemas
+const MERGED_KEYWORDS: &[&str] = &["type", "properties", "required", "x-property-order"];
+
+/// Order scalar JSON values of the same type
+fn compare_scalars(left: &Value, right: &Value) -> Ordering {
+    match (left, right) {
+        (Value::String(left), Value::String(right)) => left.cmp(right),
+        (Value::Number(left), Value::Number(right)) => left
+            .as_f64()
+            .partial_cmp(&right.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::Bool(left), Value::Bool(right)) => left.cmp(right),
+        _ => Ordering::Equal,
+    }
+}
+
+/// Keywords derived from observed values, which are joined when schemas are merged
+struct ValueKeywords {
+    /// Enum values, if any
+    values: Option<Vec<Value>>,
+    examples: Vec<Value>,
+    /// Observed string length bounds, if any
+    length: Option<(u64, u64)>,
+    /// Observed numeric bounds, if any
+    range: Option<(Value, Value)>,
+    /// Observed array length bounds, if any
+    items: Option<(u64, u64)>,
+}
+
+impl ValueKeywords {
+    /// Remove value keywords from the schema
+    fn take(
+        schema: &mut Value,
+        with_length: bool,
+        with_range: bool,
+        with_items: bool,
+    ) -> ValueKeywords {
+        let length = if with_length {
+            take_bounds(schema, "minLength", "maxLength")
+        } else {
+            None
+        };
+        let range = if with_range { take_range(schema) } else { None };
+        let items = if with_items {
+            take_bounds(schema, "minItems", "maxItems")
+        } else {
+            None
+        };
+        match schema.as_object_mut() {
+            Some(map) => ValueKeywords {
+                values: map.remove("enum").and_then(into_array),
+                examples: map
+                    .remove("examples")
+                    .and_then(into_array)
+                    .unwrap_or_default(),
+                length,
+                range,
+                items,
+            },
+            None => ValueKeywords {
+                values: None,
+                examples: vec![],
+                length: None,
+                range: None,
+                items: None,
+            },
+        }
+    }
+
+    /// Join with keywords of another schema
+    fn join(&mut self, other: ValueKeywords) {
+        match (self.values.as_mut(), other.values) {
+            (Some(known), Some(values)) => extend_unique(known, values),
+            // Without `enum` any value of the type is possible
+            _ => self.values = None,
+        }
+        extend_unique(&mut self.examples, other.examples);
+        self.length = join_bounds(self.length, other.length);
+        self.range = match (self.range.take(), other.range) {
+            (Some((min, max)), Some((other_min, other_max))) => Some((
+                core::cmp::min_by(min, other_min, compare_scalars),
+                core::cmp::max_by(max, other_max, compare_scalars),
+            )),
+            _ => None,
+        };
+        self.items = join_bounds(self.items, other.items);
+    }
+}
+
+/// Numeric bounds that are inferred from observed values
+const BOUND_KEYWORDS: &[&str] = &["minimum", "exclusiveMinimum"];
+
+/// Keywords that don't affect validation
+const ANNOTATIONS: &[&str] = &["default", "examples", "title", "description", "$comment"];
+
+/// Whether the schema has the given type and no other keywords except annotations
+fn is_bare(schema: &Value, expected_type: &str) -> bool {
+    schema.as_object().is_some_and(|map| {
+        map.get("type").and_then(Value::as_str) == Some(expected_type)
+            && map
+                .keys()
+                .all(|keyword| keyword == "type" || ANNOTATIONS.contains(&keyword.as_str()))
+    })
+}
+
+fn into_array(value: Value) -> Option<Vec<Value>> {
+    match value {
+        Value::Array(values) => Some(values),
+        _ => None,
+    }
+}
+
+/// Add values that are not in `known` yet
+fn extend_unique(known: &mut Vec<Value>, values: Vec<Value>) {
+    for value in values {
+        if !known.contains(&value) {
+            known.push(value)
+        }
+    }
+}
+
+/// The string value of the tag property in a variant schema of a tagged union
+fn tag_value<'a>(schema: &'a Value, key: &str) -> Option<&'a str> {
+    let tag = schema.get("properties")?.get(key)?;
+    match tag.get("const") {
+        Some(value) => value.as_str(),
+        None => match tag.get("enum")?.as_array()?.as_slice() {
+            [value] => value.as_str(),
+            _ => None,
+        },
+    }
+}
+
+/// The tag property of variant schemas, i.e. the one with a tag value in every variant
+fn find_tag_key(variants: &Value) -> Option<&str> {
+    let variants = variants.as_array()?;
+    variants
+        .first()?
+        .get("properties")?
+        .as_object()?
+        .keys()
+        .map(String::as_str)
+        .find(|key| {
+            variants
+                .iter()
+                .all(|variant| tag_value(variant, key).is_some())
+        })
+}
+
+/// The maximum number of distinct values of an automatically detected tag
+const MAX_TAG_VALUES: usize = 10;
+
+/// The maximum number of definitions extracted by `extract_definitions`, so input with many
+/// distinct repeated objects doesn't produce a huge definitions map
+const MAX_DEFINITIONS: usize = 1000;
+
+/// Tag values in the order of first appearance with indices of objects having them
+type Variants<'a> = Vec<(&'a str, Vec<usize>)>;
+
+/// Find a tag property of objects and group the objects by its values.
+/// A tag is a string property present in all the objects. Unless it is given explicitly,
+/// it should have from two to `MAX_TAG_VALUES` distinct values, and objects with different values
+/// should have different sets of properties
+fn find_discriminator<'a>(
+    objects: &[&'a Map<String, Value>],
+    key: Option<&'a str>,
+) -> Option<(&'a str, Variants<'a>)> {
+    let candidates: Vec<&'a str> = match key {
+        Some(key) => vec![key],
+        None => objects.first()?.keys().map(String::as_str).collect(),
+    };
+    candidates.into_iter().find_map(|candidate| {
+        let mut variants: Variants<'a> = Vec::new();
+        for (idx, object) in objects.iter().enumerate() {
+            let tag = object.get(candidate)?.as_str()?;
+            match variants.iter_mut().find(|(known, _)| *known == tag) {
+                Some((_, indices)) => indices.push(idx),
+                None => variants.push((tag, vec![idx])),
+            }
+        }
+        if variants.is_empty() {
+            return None;
+        }
+        if key.is_none() {
+            let shapes: Vec<Vec<&String>> = variants
+                .iter()
+                .map(|(_, indices)| {
+                    let mut keys: Vec<&String> = indices
+                        .iter()
+                        .flat_map(|&idx| objects[idx].keys())
+                        .collect();
+                    keys.sort();
+                    keys.dedup();
+                    keys
+                })
+                .collect();
+            if variants.len() < 2
+                || variants.len() > MAX_TAG_VALUES
+                || shapes.iter().all(|shape| *shape == shapes[0])
+            {
+                return None;
+            }
+        }
+        Some((candidate, variants))
+    })
+}
+
+/// Rename properties of object schemas that differ only in case to the first seen name.
+/// If one schema has several such properties, only the first one is kept
+fn unify_key_case(schemas: &[&Value]) -> Vec<Value> {
+    let mut canonical: BTreeMap<String, String> = BTreeMap::new();
+    let mut rename = |name: &str| {
+        canonical
+            .entry(name.to_lowercase())
+            .or_insert_with(|| name.to_owned())
+            .clone()
+    };
+    schemas
+        .iter()
+        .map(|&schema| {
+            let mut schema = schema.clone();
+            if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+                let mut renamed = Map::new();
+                for (name, property) in core::mem::take(properties) {
+                    renamed.entry(rename(&name)).or_insert(property);
+                }
+                *properties = renamed;
+            }
+            for keyword in &["required", "x-property-order"] {
+                if let Some(Value::Array(names)) = schema.get_mut(*keyword) {
+                    let mut renamed: Vec<Value> = Vec::with_capacity(names.len());
+                    for name in names.iter().filter_map(Value::as_str) {
+                        let name = Value::String(rename(name));
+                        if !renamed.contains(&name) {
+                            renamed.push(name);
+                        }
+                    }
+                    *names = renamed;
+                }
+            }
+            schema
+        })
+        .collect()
+}
+
+/// Marks schemas of empty strings until formats of joined schemas are reconciled, it is never
+/// emitted
+const EMPTY_STRING_MARKER: &str = "x-empty-string";
+
+fn remove_empty_string_markers(schema: &mut Value) {
+    walk::walk_mut(schema, &mut |schema| {
+        if let Value::Object(map) = schema {
+            map.remove(EMPTY_STRING_MARKER);
+        }
+    });
+}
+
+/// Whether the schema is the one of an empty object with `open_empty_objects` set
+fn is_open_object(schema: &Value) -> bool {
+    schema.as_object().is_some_and(|map| map.len() == 1) && schema["type"] == "object"
+}
+
+/// Whether the JSON Pointer matches the pattern, where `*` matches any single reference token
+fn pointer_matches(pattern: &str, pointer: &str) -> bool {
+    let mut pattern_tokens = pattern.split('/');
+    let mut tokens = pointer.split('/');
+    loop {
+        match (pattern_tokens.next(), tokens.next()) {
+            (Some(expected), Some(token)) if expected == "*" || expected == token => continue,
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+fn is_empty_schema(schema: &Value) -> bool {
+    schema.as_object().is_some_and(Map::is_empty)
+}
+
+/// Add definitions to the root schema, keeping existing ones
+fn add_definitions(schema: &mut Value, keyword: &str, definitions: Map<String, Value>) {
+    if definitions.is_empty() {
+        return;
+    }
+    match schema.get_mut(keyword) {
+        Some(Value::Object(existing)) => existing.extend(definitions),
+        _ => schema[keyword] = Value::Object(definitions),
+    }
+}
+
+/// Sorted property names of an object schema with properties
+fn property_names(schema: &Value) -> Option<Vec<&String>> {
+    match schema.get("properties") {
+        Some(Value::Object(properties)) if !properties.is_empty() && schema["type"] == "object" => {
+            Some(properties.keys().collect())
+        }
+        _ => None,
+    }
+}
+
+/// Build a definition for a recursive object schema, where nested objects with the same
+/// properties or a subset of them are replaced by `reference`.
+/// Returns `None` if there are no nested objects with exactly the same properties
+fn recursive_definition(schema: &Value, reference: &Value) -> Option<Value> {
+    let names: Vec<String> = property_names(schema)?.into_iter().cloned().collect();
+    let is_nested = |candidate: &Value| {
+        property_names(candidate)
+            .is_some_and(|candidate| candidate.iter().all(|name| names.contains(name)))
+    };
+    let mut nested = vec![];
+    let mut recursive = false;
+    walk::subschemas_mut(&mut schema.clone(), &mut |subschema| {
+        walk::walk_mut(subschema, &mut |subschema| {
+            if is_nested(subschema) {
+                recursive |= property_names(subschema)
+                    .is_some_and(|other| other == names.iter().collect::<Vec<_>>());
+                nested.push(subschema.clone());
+            }
+        })
+    });
+    if !recursive {
+        return None;
+    }
+    let replace_nested = |mut schema: Value| {
+        walk::subschemas_mut(&mut schema, &mut |subschema| {
+            walk::walk_top_down_mut(subschema, &mut |subschema| {
+                if is_nested(subschema) {
+                    *subschema = reference.clone();
+                    false
+                } else {
+                    true
+                }
+            })
+        });
+        schema
+    };
+    let definition = replace_nested(schema.clone());
+    Some(nested.into_iter().fold(definition, |definition, schema| {
+        refine::refine_schema(&definition, &replace_nested(schema))
+    }))
+}
+
+/// Remove duplicate schemas, keeping the first occurrence
+fn dedup(schemas: &mut Vec<Value>) {
+    let mut unique = Vec::with_capacity(schemas.len());
+    for schema in schemas.drain(..) {
+        if !unique.contains(&schema) {
+            unique.push(schema)
+        }
+    }
+    *schemas = unique;
+}
+
+fn collect_required<'a>(known_required: &mut Vec<Vec<&'a str>>, item: &'a Value) {
+    let required = item
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    known_required.push(required);
+}
+
+/// Fill required properties
+/// There will be only properties that are common to all objects, in the order of the first one
+fn fill_required(map: &mut Map<String, Value>, known_required: Vec<Vec<&str>>) {
+    if let Some(first) = known_required.first() {
+        let common_required = first
+            .iter()
+            .filter(|&k| known_required.iter().all(|s| s.contains(k)))
+            .map(|x| json!(x))
+            .collect::<Vec<Value>>();
+        if !common_required.is_empty() {
+            map.insert("required".into(), Value::Array(common_required));
+        }
+    }
+}
+
+/// Infer a pattern shared by all keys of a map-like object.
+fn infer_key_pattern<'a>(mut keys: impl Iterator<Item = &'a String>) -> Option<&'static str> {
+    let first = keys.next()?;
+    let pattern = key_pattern(first)?;
+    if keys.all(|key| key_pattern(key) == Some(pattern)) {
+        Some(pattern)
+    } else {
+        None
+    }
+}
+
+/// Checks whether a key follows a naming convention
+type KeyStyle = fn(&str) -> bool;
+
+/// Naming conventions of keys with their patterns, from the most specific ones
+const KEY_STYLES: &[(KeyStyle, &str)] = &[
+    (is_snake_case, "^[a-z][a-z0-9]*(_[a-z0-9]+)*$"),
+    (is_camel_case, "^[a-z][a-zA-Z0-9]*$"),
+    (is_kebab_case, "^[a-z][a-z0-9]*(-[a-z0-9]+)*$"),
+    (is_pascal_case, "^[A-Z][a-zA-Z0-9]*$"),
+    (is_screaming_snake_case, "^[A-Z][A-Z0-9]*(_[A-Z0-9]+)*$"),
+];
+
+/// Describe keys that follow one naming convention, e.g. snake_case, by `propertyNames` with
+/// a pattern and the maximal length. Keys of mixed conventions are not described
+fn infer_key_style<'a>(keys: impl Iterator<Item = &'a String> + Clone) -> Option<Value> {
+    let max_length = keys.clone().map(|key| key.chars().count()).max()?;
+    let (_, pattern) = KEY_STYLES
+        .iter()
+        .find(|(matches, _)| keys.clone().all(|key| matches(key)))?;
+    Some(json!({"pattern": pattern, "maxLength": max_length}))
+}
+
+/// Words of lowercase letters and digits, starting with a letter and separated by `separator`
+fn is_separated_words(key: &str, separator: char, is_case: fn(&char) -> bool) -> bool {
+    key.starts_with(|char: char| char.is_ascii_alphabetic())
+        && key.split(separator).all(|word| {
+            !word.is_empty()
+                && word
+                    .chars()
+                    .all(|char| is_case(&char) || char.is_ascii_digit())
+        })
+}
+
+fn is_snake_case(key: &str) -> bool {
+    is_separated_words(key, '_', char::is_ascii_lowercase)
+}
+
+fn is_kebab_case(key: &str) -> bool {
+    is_separated_words(key, '-', char::is_ascii_lowercase)
+}
+
+fn is_screaming_snake_case(key: &str) -> bool {
+    is_separated_words(key, '_', char::is_ascii_uppercase)
+}
+
+fn is_camel_case(key: &str) -> bool {
+    key.starts_with(|char: char| char.is_ascii_lowercase())
+        && key.chars().all(|char| char.is_ascii_alphanumeric())
+}
+
+fn is_pascal_case(key: &str) -> bool {
+    key.starts_with(|char: char| char.is_ascii_uppercase())
+        && key.chars().all(|char| char.is_ascii_alphanumeric())
+}
+
+fn key_pattern(key: &str) -> Option<&'static str> {
+    if is_uuid(key) {
+        return Some(
+            "^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+        );
+    }
+    // Keys are checked against the emitted patterns too, as formats accept more, e.g. `+1`
+    // is an integer and dates may have years with more digits
+    match infer_format(key, Format::DEFAULT, EmailStrictness::Strict) {
+        Some("integer") => Some("^[+-]?[0-9]+$"),
+        Some("date") if is_date_key(key) => Some("^[0-9]{4}-[0-9]{2}-[0-9]{2}$"),
+        _ => None,
+    }
+}
+
+/// Whether the key has the shape of `^[0-9]{4}-[0-9]{2}-[0-9]{2}$`
+fn is_date_key(key: &str) -> bool {
+    let bytes = key.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(idx, byte)| match idx {
+            4 | 7 => *byte == b'-',
+            _ => byte.is_ascii_digit(),
+        })
+}
+
+/// Check whether the given string is a hyphenated UUID, e.g. "67e55044-10b1-426f-9247-bb680e5fe0c8"
+fn is_uuid(string: &str) -> bool {
+    let bytes = string.as_bytes();
+    bytes.len() == 36
+        && bytes.iter().enumerate().all(|(idx, byte)| match idx {
+            8 | 13 | 18 | 23 => *byte == b'-',
+            _ => byte.is_ascii_hexdigit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_json(data: &[(Value, Value)]) {
+        for (value, expected) in data {
+            assert_eq!(infer(value), *expected);
+        }
+    }
+
+    #[test]
+    fn test_primitive_types() {
+        let cases = [
+            (
+                json!(null),
+                json!({"type": "null", "$schema": "http://json-schema.org/draft-07/schema#"}),
+            ),
+            (
+                json!(1.35),
+                json!({"type": "number", "$schema": "http://json-schema.org/draft-07/schema#"}),
+            ),
+            (
+                json!(5),
+                json!({"type": "integer", "$schema": "http://json-schema.org/draft-07/schema#"}),
+            ),
+            (
+                json!("Test".to_owned()),
+                json!({"type": "string", "$schema": "http://json-schema.org/draft-07/schema#"}),
+            ),
+        ];
+        assert_json(&cases);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_string_format() {
+        let cases = [
+            (
+                json!("1"),
+                json!({"type": "string", "format": "integer", "$schema": "http://json-schema.org/draft-07/schema#"}),
+            ),
+            (
+                json!("2020-01-01"),
+                json!({"type": "string", "format": "date", "$schema": "http://json-schema.org/draft-07/schema#"}),
+            ),
+            (
+                json!("2018-11-13T20:20:39+00:00"),
+                json!({"type": "string", "format": "date-time", "$schema": "http://json-schema.org/draft-07/schema#"}),
+            ),
+        ];
+        assert_json(&cases);
+    }
+
+    #[test]
+    fn test_disabled_string_format() {
+        let data = json!("2020-01-01");
+        let schema = JSONSchema::new(&data).detect_format(false);
+        assert_eq!(
+            schema.infer(),
+            json!({"type": "string", "$schema": "http://json-schema.org/draft-07/schema#"})
+        );
+    }
+
+    #[test]
+    fn test_disabled_string_format_nested() {
+        let cases = [
+            (
+                json!({"key": "2020-01-01"}),
+                json!({"type": "object", "properties": {"key": {"type": "string"}}, "required": ["key"], "$schema": "http://json-schema.org/draft-07/schema#"}),
+            ),
+            (
+                json!(["2020-01-01"]),
+                json!({"type": "array", "items": {"type": "string"}, "$schema": "http://json-schema.org/draft-07/schema#"}),
+            ),
+        ];
+        for (value, expected) in &cases {
+            let schema = JSONSchema::new(value).detect_format(false);
+            assert_eq!(schema.infer(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_array_primitive() {
+        let cases = [
+            (
+                json!(["test", "item"]),
+                json!({"type": "array", "items": {"type": "string"}, "$schema": "http://json-schema.org/draft-07/schema#"}),
+            ),
+            (
+                json!(["test", "item", 1]),
+                json!({
+                  "type": "array",
+                  "items": {
+                    "anyOf": [
+                      {"type": "string"},
+                      {"type": "integer"}
+                    ]
+                  },
+                  "$schema": "http://json-schema.org/draft-07/schema#"
+                }),
+            ),
+        ];
+        assert_json(&cases);
+    }
+
+    #[test]
+    fn test_object_primitive() {
+        let cases = [
+            (
+                json!({"key": true}),
+                json!({
+                  "type": "object",
+                  "properties": {
+                      "key": {"type": "boolean"}
+                  },
+                  "required": ["key"],
+                  "$schema": "http://json-schema.org/draft-07/schema#"
+                }),
+            ),
+            (
+                json!({"key1": true, "key2": 1}),
+                json!({
+                  "type": "object",
+                  "properties": {
+                      "key1": {"type": "boolean"},
+                      "key2": {"type": "integer"}
+                  },
+                  "required": ["key1", "key2"],
+                  "$schema": "http://json-schema.org/draft-07/schema#"
+                }),
+            ),
+        ];
+        assert_json(&cases);
+    }
+
+    #[test]
+    fn test_array_complex() {
+        let cases = [
+            (
+                json!([{"a": 1}, {"a": 2}]),
+                json!({
+                  "type": "array",
+                  "items": {
+                    "type": "object",
+                    "properties": {
+                      "a": {"type": "integer"}
+                    },
+                    "required": ["a"]
+                  },
+                  "$schema": "http://json-schema.org/draft-07/schema#"
+                }),
+            ),
+            (
+                json!([{"a": 1}, {"a": null}, {"a": 2}]),
+                json!({
+                  "type": "array",
+                  "items": {
+                    "type": "object",
+                    "required": ["a"],
+                    "properties": {
+                      "a": {
+                        "anyOf": [
+                          {"type": "null"},
+                          {"type": "integer"},
+                        ]
+                      }
+                    }
+                  },
+                  "$schema": "http://json-schema.org/draft-07/schema#"
+                }),
+            ),
+            // Proper required detection.
+            (
+                json!([{"a": 1}, {"b": "test"}]),
+                json!({
+                  "type": "array",
+                  "items": {
+                    "type": "object",
+                    "properties": {
+                      "a": {"type": "integer"},
+                      "b": {"type": "string"}
+                    }
+                  },
+                  "$schema": "http://json-schema.org/draft-07/schema#"
+                }),
+            ),
+        ];
+        assert_json(&cases);
+    }
+
+    #[test]
+    fn test_infer_via_schema() {
+        let data = json!(null);
+        let schema = JSONSchema::new(&data);
+        assert_eq!(
+            schema.infer(),
+            json!({"type": "null", "$schema": "http://json-schema.org/draft-07/schema#"})
+        );
+    }
+
+    #[test]
+    fn test_map_property_names() {
+        let data = json!({
+            "67e55044-10b1-426f-9247-bb680e5fe0c8": {"name": "foo"},
+            "a0b1c2d3-e4f5-4a6b-8c7d-9e0f1a2b3c4d": {"name": "bar"}
+        });
+        let schema = JSONSchema::new(&data).map_threshold(2);
+        assert_eq!(
+            schema.infer(),
+            json!({
+              "type": "object",
+              "additionalProperties": {
+                "type": "object",
+                "properties": {"name": {"type": "string"}},
+                "required": ["name"]
+              },
+              "propertyNames": {
+                "pattern": "^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$"
+              },
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+    }
+
+    #[test]
+    fn test_map_mixed_keys() {
+        let data = json!({
+            "67e55044-10b1-426f-9247-bb680e5fe0c8": 1,
+            "foo": 2
+        });
+        let schema = JSONSchema::new(&data).map_threshold(2);
+        assert_eq!(
+            schema.infer(),
+            json!({
+              "type": "object",
+              "additionalProperties": {"type": "integer"},
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+    }
+
+    #[test]
+    fn test_map_integer_keys() {
+        let data = json!({"+1": true, "-2": false, "3": true});
+        let inferred = JSONSchema::new(&data).map_threshold(2).infer();
+        let pattern = &inferred["propertyNames"]["pattern"];
+        assert_eq!(pattern, "^[+-]?[0-9]+$");
+        // The schema accepts its own input
+        #[cfg(feature = "std")]
+        {
+            let pattern = regex::Regex::new(pattern.as_str().unwrap()).unwrap();
+            for key in data.as_object().unwrap().keys() {
+                assert!(pattern.is_match(key), "{}", key);
+            }
+        }
+        assert!(is_date_key("2020-01-01"));
+        assert!(!is_date_key("2020-1-1"));
+        assert!(!is_date_key("+12020-01-01"));
+    }
+
+    #[test]
+    fn test_map_below_threshold() {
+        let data = json!({"a": 1, "b": 2});
+        let schema = JSONSchema::new(&data).map_threshold(3);
+        assert_eq!(
+            schema.infer(),
+            json!({
+              "type": "object",
+              "properties": {"a": {"type": "integer"}, "b": {"type": "integer"}},
+              "required": ["a", "b"],
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_schemas() {
+        let first = json!({
+            "type": "object",
+            "properties": {"a": {"type": "integer"}, "b": {"type": "string"}},
+            "required": ["a", "b"]
+        });
+        let second = json!({
+            "type": "object",
+            "properties": {"a": {"type": "null"}, "b": {"type": "string"}},
+            "required": ["a"]
+        });
+        assert_eq!(
+            merge_schemas(&[&first, &second]),
+            Some(json!({
+                "type": "object",
+                "properties": {
+                    "a": {"anyOf": [{"type": "integer"}, {"type": "null"}]},
+                    "b": {"type": "string"}
+                },
+                "required": ["a"]
+            }))
+        );
+    }
+
+    #[test]
+    fn test_merge_schemas_without_required() {
+        let first = json!({"type": "object", "properties": {"a": {"type": "integer"}}});
+        let second =
+            json!({"type": "object", "properties": {"a": {"type": "integer"}}, "required": ["a"]});
+        assert_eq!(
+            merge_schemas(&[&first, &second]),
+            Some(json!({"type": "object", "properties": {"a": {"type": "integer"}}}))
+        );
+    }
+
+    #[test]
+    fn test_merge_schemas_not_objects() {
+        let first = json!({"type": "object", "properties": {}});
+        let second = json!({"type": "string"});
+        assert_eq!(merge_schemas(&[&first, &second]), None);
+        assert_eq!(merge_schemas(&[&json!({})]), None);
+        assert_eq!(merge_schemas(&[]), None);
+    }
+
+    #[test]
+    fn test_object_hash_collision() {
+        // XOR-ing entry hashes of "properties" makes these two objects collide
+        let first = json!([{"a": {"k34196": null}, "b": {"k2669530": null}}]);
+        let second = json!([{"a": {"k7238792": null}, "b": {"k4825722": null}}]);
+        assert_ne!(
+            canonical_hash(&infer(&first)["items"]),
+            canonical_hash(&infer(&second)["items"])
+        );
+        let schema = infer(&json!([first, second]));
+        let property = &schema["items"]["items"]["properties"]["a"];
+        // Both objects are described, neither is lost to the collision
+        assert_eq!(
+            property["properties"],
+            json!({"k34196": {"type": "null"}, "k7238792": {"type": "null"}})
+        );
+    }
+
+    #[test]
+    fn test_object_hash_order_independent() {
+        let first: Value = serde_json::from_str(r#"{"a": 1, "b": [true, null]}"#).unwrap();
+        let second: Value = serde_json::from_str(r#"{"b": [true, null], "a": 1}"#).unwrap();
+        assert_eq!(canonical_hash(&first), canonical_hash(&second));
+        assert_ne!(
+            canonical_hash(&first),
+            canonical_hash(&json!({"a": 1, "b": [null, true]}))
+        );
+    }
+
+    #[test]
+    fn test_unify_int_float() {
+        let data = json!([1, 1.0, 2]);
+        let schema = JSONSchema::new(&data).unify_int_float(true);
+        assert_eq!(
+            schema.infer(),
+            json!({"type": "array", "items": {"type": "number"}, "$schema": "http://json-schema.org/draft-07/schema#"})
+        );
+        let data = json!([{"a": 1}, {"a": 1.5}, {"a": "foo"}]);
+        let schema = JSONSchema::new(&data).unify_int_float(true);
+        assert_eq!(
+            schema.infer(),
+            json!({
+              "type": "array",
+              "items": {
+                "type": "object",
+                "properties": {
+                  "a": {"anyOf": [{"type": "number"}, {"type": "string"}]}
+                },
+                "required": ["a"]
+              },
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+    }
+
+    #[test]
+    fn test_int_float_not_unified() {
+        let data = json!([{"a": 1}, {"a": 1.5}]);
+        let schema = JSONSchema::new(&data)
+            .enum_threshold(5)
+            .unify_int_float(false);
+        let branches = schema.infer()["items"]["properties"]["a"]["anyOf"]
+            .as_array()
+            .unwrap()
+            .clone();
+        assert_eq!(branches.len(), 2);
+        assert!(branches.contains(&json!({"type": "integer", "enum": [1]})));
+        assert!(branches.contains(&json!({"type": "number", "enum": [1.5]})));
+        let schema = JSONSchema::new(&data)
+            .enum_threshold(5)
+            .unify_int_float(true);
+        assert_eq!(
+            schema.infer()["items"]["properties"]["a"],
+            json!({"type": "number", "enum": [1, 1.5]})
+        );
+        let data = json!([1, 2]);
+        let schema = JSONSchema::new(&data).unify_int_float(true);
+        assert_eq!(schema.infer()["items"], json!({"type": "integer"}));
+    }
+
+    #[test]
+    fn test_mixed_numeric_array() {
+        let data = json!([1, 2.5, 3]);
+        assert_eq!(
+            infer(&data),
+            json!({"type": "array", "items": {"type": "number"}, "$schema": "http://json-schema.org/draft-07/schema#"})
+        );
+        let data = json!([{"a": 1}, {"a": 2.5}]);
+        assert_eq!(
+            infer(&data)["items"]["properties"]["a"],
+            json!({"type": "number"})
+        );
+        let data = json!([1, 2.5, "foo"]);
+        let branches = infer(&data)["items"]["anyOf"].as_array().unwrap().clone();
+        assert_eq!(branches.len(), 2);
+        assert!(!branches.contains(&json!({"type": "integer"})));
+    }
+
+    #[test]
+    fn test_infer_to_string() {
+        let schema = infer_to_string(r#"{"a": [1, 2]}"#).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&schema).unwrap(),
+            json!({
+              "type": "object",
+              "properties": {"a": {"type": "array", "items": {"type": "integer"}}},
+              "required": ["a"],
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+        assert!(infer_to_string("{").is_err());
+    }
+
+    #[test]
+    fn test_parallel_and_serial_are_identical() {
+        let data = json!([
+            {"a": 1, "b": [1, "foo", {"c": null}]},
+            {"a": 1.5, "d": "2020-01-01"},
+            [1, 2, 3, 4, 5, 6, 7, 8, 9, 10],
+            "2018-11-13T20:20:39+00:00",
+            null,
+            true,
+            {"a": "bar", "b": []},
+            [{"x": 1}, {"y": 2}, {"x": 3, "y": 4}, {}, {}, {}, {}, {}, {}],
+            42,
+            {"b": [null, null, null, null, null, null, null, null, null, 1]}
+        ]);
+        let schema = JSONSchema::new(&data);
+        let array = data.as_array().unwrap();
+        assert_eq!(
+            schema.infer_items(array, &Location::Root).unwrap(),
+            schema
+                .infer_items_serial(&schema.distinct_items(array), &Location::Root)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parallel_threshold_boundaries() {
+        for length in &[8, 9] {
+            let items: Vec<Value> = (0..*length)
+                .map(|idx| json!({"id": idx, "tags": ["foo", idx % 2 == 0], "parent": null}))
+                .collect();
+            let data = Value::Array(items);
+            let expected = JSONSchema::new(&data)
+                .parallel_threshold(usize::MAX)
+                .infer();
+            for threshold in &[0, 7, 8, 9] {
+                let schema = JSONSchema::new(&data).parallel_threshold(*threshold);
+                assert_eq!(schema.infer(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_emit_property_order() {
+        let data: Value =
+            serde_json::from_str(r#"{"name": "foo", "id": 1, "active": true}"#).unwrap();
+        let schema = JSONSchema::new(&data).emit_property_order(true).infer();
+        let input_order: Vec<&String> = data.as_object().unwrap().keys().collect();
+        assert_eq!(schema["x-property-order"], json!(input_order));
+        assert!(infer(&data).get("x-property-order").is_none());
+    }
+
+    #[test]
+    fn test_emit_property_order_merged() {
+        let data = json!([{"b": 1, "a": 2}, {"c": 3, "b": 4, "a": 5}]);
+        let schema = JSONSchema::new(&data).emit_property_order(true).infer();
+        assert_eq!(schema["items"]["x-property-order"], json!(["a", "b", "c"]));
+    }
+
+    #[test]
+    fn test_detect_defaults() {
+        let data = json!([
+            {"kind": "user", "id": 1},
+            {"kind": "user", "id": 2},
+            {"id": 3}
+        ]);
+        let schema = JSONSchema::new(&data).detect_defaults(true);
+        assert_eq!(
+            schema.infer(),
+            json!({
+              "type": "array",
+              "items": {
+                "type": "object",
+                "properties": {
+                  "kind": {"type": "string", "default": "user"},
+                  "id": {"type": "integer"}
+                },
+                "required": ["id"]
+              },
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+    }
+
+    #[test]
+    fn test_default_is_not_const() {
+        let data = json!([{"a": true}, {"a": true}]);
+        let inferred = JSONSchema::new(&data).detect_defaults(true).infer();
+        let property = &inferred["items"]["properties"]["a"];
+        assert_eq!(property, &json!({"type": "boolean", "default": true}));
+        assert!(property.get("const").is_none());
+        let data = json!([{"a": 1}, {"a": "foo"}]);
+        let inferred = JSONSchema::new(&data).detect_defaults(true).infer();
+        assert_eq!(
+            inferred["items"]["properties"]["a"],
+            json!({"anyOf": [{"type": "string"}, {"type": "integer"}]})
+        );
+        let data = json!([{"a": 1}, {"a": 1}]);
+        assert_eq!(
+            infer(&data)["items"]["properties"]["a"],
+            json!({"type": "integer"})
+        );
+    }
+
+    #[test]
+    fn test_enum_threshold() {
+        let data = json!([
+            {"color": "red", "size": 1},
+            {"color": "green", "size": 2},
+            {"color": "red", "size": 3}
+        ]);
+        let schema = JSONSchema::new(&data).enum_threshold(2);
+        assert_eq!(
+            schema.infer()["items"]["properties"],
+            json!({
+              "color": {"type": "string", "enum": ["green", "red"]},
+              "size": {"type": "integer"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_enum_types() {
+        let data = json!([{"flag": true, "size": 1}, {"flag": false, "size": 2}]);
+        let schema = JSONSchema::new(&data).enum_threshold(1);
+        assert_eq!(
+            schema.infer()["items"]["properties"],
+            json!({"flag": {"type": "boolean"}, "size": {"type": "integer"}})
+        );
+        let schema = JSONSchema::new(&data).enum_threshold(5);
+        assert_eq!(
+            schema.infer()["items"]["properties"],
+            json!({"flag": {"type": "boolean"}, "size": {"type": "integer", "enum": [1, 2]}})
+        );
+        let schema = JSONSchema::new(&data)
+            .enum_threshold(5)
+            .enum_types(&["string", "boolean"]);
+        assert_eq!(
+            schema.infer()["items"]["properties"],
+            json!({"flag": {"type": "boolean", "enum": [false, true]}, "size": {"type": "integer"}})
+        );
+    }
+
+    #[test]
+    fn test_hook() {
+        let data = json!({"user": {"name": "foo", "tags": [{"id": 1}]}});
+        let schema = JSONSchema::new(&data).hook(|schema, context| {
+            if schema["type"] == "object" {
+                schema["x-pointer"] = json!(context.pointer());
+                schema["x-size"] = json!(context.value().as_object().unwrap().len());
+            }
+        });
+        assert_eq!(
+            schema.infer(),
+            json!({
+              "type": "object",
+              "properties": {
+                "user": {
+                  "type": "object",
                   "properties": {
-                      "key1": {"type": "boolean"},
-                      "key2": {"type": "integer"}
+                    "name": {"type": "string"},
+                    "tags": {
+                      "type": "array",
+                      "items": {
+                        "type": "object",
+                        "properties": {"id": {"type": "integer"}},
+                        "required": ["id"],
+                        "x-pointer": "/user/tags/0",
+                        "x-size": 1
+                      }
+                    }
                   },
-                  "required": ["key1", "key2"],
-                  "$schema": "http://json-schema.org/draft-07/schema#"
-                }),
+                  "required": ["name", "tags"],
+                  "x-pointer": "/user",
+                  "x-size": 2
+                }
+              },
+              "required": ["user"],
+              "x-pointer": "",
+              "x-size": 1,
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+    }
+
+    #[test]
+    fn test_hook_merged_items() {
+        let data = json!([{"a": 1}, {"b": "a/b"}]);
+        let schema = JSONSchema::new(&data).hook(|schema, context| {
+            if schema["type"] == "object" {
+                schema["x-tag"] = json!("object");
+            }
+            if context.pointer() == "/1/b" {
+                schema["x-escaped"] = json!(true);
+            }
+        });
+        let inferred = schema.infer();
+        assert_eq!(inferred["items"]["x-tag"], "object");
+        assert_eq!(
+            inferred["items"]["properties"]["b"],
+            json!({"type": "string", "x-escaped": true})
+        );
+    }
+
+    #[test]
+    fn test_location_pointer() {
+        let root = Location::Root;
+        let key = Location::Key(&root, "a/b~c");
+        let index = Location::Index(&key, 3);
+        assert_eq!(index.pointer(), "/a~1b~0c/3");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_opt_in_formats() {
+        let data = json!({"pointer": "/a/b/0", "regex": "^[a-z]+$", "plain": "a/b"});
+        assert_eq!(
+            infer(&data)["properties"],
+            json!({
+              "pointer": {"type": "string"},
+              "regex": {"type": "string"},
+              "plain": {"type": "string"}
+            })
+        );
+        let schema = JSONSchema::new(&data)
+            .enable_format(Format::JsonPointer)
+            .enable_format(Format::Regex);
+        assert_eq!(
+            schema.infer()["properties"],
+            json!({
+              "pointer": {"type": "string", "format": "json-pointer"},
+              "regex": {"type": "string", "format": "regex"},
+              "plain": {"type": "string"}
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_formats_selection() {
+        let data = json!(["2020-01-01", "1"]);
+        let schema = JSONSchema::new(&data)
+            .formats(&[Format::Date])
+            .simplify_unions(false);
+        let branches = schema.infer()["items"]["anyOf"].as_array().unwrap().clone();
+        assert!(branches.contains(&json!({"type": "string", "format": "date"})));
+        assert!(branches.contains(&json!({"type": "string"})));
+    }
+
+    #[test]
+    fn test_type_as_array() {
+        let data = json!({"a": [1, "foo"], "b": {"c": null}, "d": [{"e": true}, {"e": 1.5}]});
+        let schema = JSONSchema::new(&data).type_as_array(true);
+        assert_eq!(
+            schema.infer(),
+            json!({
+              "type": ["object"],
+              "properties": {
+                "a": {
+                  "type": ["array"],
+                  "items": {"anyOf": [{"type": ["string"]}, {"type": ["integer"]}]}
+                },
+                "b": {
+                  "type": ["object"],
+                  "properties": {"c": {"type": ["null"]}},
+                  "required": ["c"]
+                },
+                "d": {
+                  "type": ["array"],
+                  "items": {
+                    "type": ["object"],
+                    "properties": {
+                      "e": {"anyOf": [{"type": ["number"]}, {"type": ["boolean"]}]}
+                    },
+                    "required": ["e"]
+                  }
+                }
+              },
+              "required": ["a", "b", "d"],
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+    }
+
+    #[test]
+    fn test_assume_unsigned() {
+        let cases = [
+            (
+                json!([{"a": 1}, {"a": 5}]),
+                json!({"type": "integer", "minimum": 0}),
             ),
+            (json!([{"a": -1}, {"a": 5}]), json!({"type": "integer"})),
+            (json!([{"a": 0}]), json!({"type": "integer", "minimum": 0})),
+            (json!([{"a": 1.5}]), json!({"type": "number"})),
+        ];
+        for (data, expected) in &cases {
+            let schema = JSONSchema::new(data).assume_unsigned(true);
+            assert_eq!(schema.infer()["items"]["properties"]["a"], *expected);
+        }
+        let data = json!([{"a": 1}, {"a": "foo"}]);
+        let schema = JSONSchema::new(&data).assume_unsigned(true);
+        let branches = schema.infer()["items"]["properties"]["a"]["anyOf"]
+            .as_array()
+            .unwrap()
+            .clone();
+        assert!(branches.contains(&json!({"type": "integer", "minimum": 0})));
+        assert_eq!(
+            infer(&json!({"a": 1}))["properties"]["a"],
+            json!({"type": "integer"})
+        );
+    }
+
+    #[test]
+    fn test_object_merge_strategy() {
+        let data = json!([{"a": 1}, {"b": "foo"}]);
+        let merged = JSONSchema::new(&data)
+            .object_merge_strategy(ObjectMergeStrategy::Merge)
+            .infer();
+        assert_eq!(
+            merged["items"],
+            json!({
+              "type": "object",
+              "properties": {"a": {"type": "integer"}, "b": {"type": "string"}}
+            })
+        );
+        let composed = JSONSchema::new(&data)
+            .object_merge_strategy(ObjectMergeStrategy::Compose)
+            .infer();
+        let branches = composed["items"]["anyOf"].as_array().unwrap();
+        assert_eq!(branches.len(), 2);
+        assert!(branches.contains(&json!({
+          "type": "object",
+          "properties": {"a": {"type": "integer"}},
+          "required": ["a"]
+        })));
+        assert!(branches.contains(&json!({
+          "type": "object",
+          "properties": {"b": {"type": "string"}},
+          "required": ["b"]
+        })));
+    }
+
+    #[test]
+    fn test_non_finite_numbers() {
+        // `serde_json` replaces non-finite floats with `null`, so they never reach inference.
+        // Decoders of other formats reject them, e.g. `msgpack::tests::test_non_finite`
+        let data = json!({"a": f64::NAN, "b": f64::INFINITY, "c": 1.5});
+        assert_eq!(
+            try_infer(&data).unwrap()["properties"],
+            json!({"a": {"type": "null"}, "b": {"type": "null"}, "c": {"type": "number"}})
+        );
+        assert!(Number::from_f64(f64::NAN).is_none());
+        let error = InferError::NonFiniteNumber {
+            pointer: "/a/0".into(),
+        };
+        assert_eq!(error.to_string(), "Non-finite number at '/a/0'");
+    }
+
+    #[test]
+    fn test_draft_uri() {
+        let data = json!(null);
+        let schema = JSONSchema::new(&data).draft(Draft::Draft202012);
+        assert_eq!(
+            schema.infer(),
+            json!({"type": "null", "$schema": "https://json-schema.org/draft/2020-12/schema"})
+        );
+    }
+
+    #[test]
+    fn test_schema_uri() {
+        let data = json!({"a": 1});
+        let uri = "https://example.com/meta/schema";
+        let schema = JSONSchema::new(&data).schema_uri(uri);
+        assert_eq!(schema.infer()["$schema"], uri);
+        let schema = JSONSchema::new(&data).schema_uri(uri).draft(Draft::Draft4);
+        assert_eq!(schema.infer()["$schema"], uri);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_array_items_format() {
+        let data = json!({"dates": ["2020-01-01", "2020-02-02"], "date": "2020-03-03"});
+        let inferred = infer(&data);
+        assert_eq!(
+            inferred["properties"]["dates"],
+            json!({"type": "array", "items": {"type": "string", "format": "date"}})
+        );
+        assert_eq!(
+            inferred["properties"]["dates"]["items"],
+            inferred["properties"]["date"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_examples() {
+        let data = json!({
+            "dates": ["2020-01-01", "2020-02-02", "2020-01-01", "2020-03-03"],
+            "objects": [{"date": "2020-01-01"}, {"date": "2020-02-02"}, {"date": "2020-03-03"}]
+        });
+        let inferred = JSONSchema::new(&data).examples(2).infer();
+        let items = &inferred["properties"]["dates"]["items"];
+        let property = &inferred["properties"]["objects"]["items"]["properties"]["date"];
+        assert_eq!(items["type"], "string");
+        assert_eq!(items["format"], "date");
+        assert_eq!(items["examples"].as_array().unwrap().len(), 2);
+        assert_eq!(property["type"], "string");
+        assert_eq!(property["format"], "date");
+        assert_eq!(property["examples"].as_array().unwrap().len(), 2);
+        let data = json!([1, 2.5, "foo"]);
+        let inferred = JSONSchema::new(&data).examples(5).infer();
+        let branches = inferred["items"]["anyOf"].as_array().unwrap();
+        assert_eq!(branches.len(), 2);
+        assert!(branches.contains(&json!({"type": "string", "examples": ["foo"]})));
+    }
+
+    #[test]
+    fn test_open_empty_objects() {
+        let data = json!({});
+        assert_eq!(
+            JSONSchema::new(&data).open_empty_objects(true).infer(),
+            json!({"type": "object", "$schema": "http://json-schema.org/draft-07/schema#"})
+        );
+        let data = json!({"a": {}, "b": [{}]});
+        let inferred = JSONSchema::new(&data).open_empty_objects(true).infer();
+        assert_eq!(inferred["properties"]["a"], json!({"type": "object"}));
+        assert_eq!(
+            inferred["properties"]["b"],
+            json!({"type": "array", "items": {"type": "object"}})
+        );
+    }
+
+    #[test]
+    fn test_open_empty_objects_merged() {
+        let data = json!([{}, {"a": 1}]);
+        let inferred = JSONSchema::new(&data).open_empty_objects(true).infer();
+        assert_eq!(
+            inferred["items"],
+            json!({"type": "object", "properties": {"a": {"type": "integer"}}})
+        );
+        // Without the option, the result is the same
+        let inferred = infer(&data);
+        assert_eq!(
+            inferred["items"],
+            json!({"type": "object", "properties": {"a": {"type": "integer"}}})
+        );
+    }
+
+    #[test]
+    fn test_repeated_items() {
+        let record = json!({"id": 1, "name": "foo", "tags": ["a", "a", "b"], "meta": {"x": 1.5}});
+        let mut items = vec![record.clone(); 100];
+        items.push(json!({"id": "2", "name": null}));
+        items.push(record);
+        items.push(json!([1, 1, "2020-01-01"]));
+        let data = Value::Array(items);
+        let mut uncached = JSONSchema::new(&data).examples(3).enum_threshold(5);
+        uncached.cache = false;
+        let cached = JSONSchema::new(&data).examples(3).enum_threshold(5);
+        assert_eq!(
+            serde_json::to_string(&cached.infer()).unwrap(),
+            serde_json::to_string(&uncached.infer()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_discriminator() {
+        let data = json!([
+            {"kind": "circle", "radius": 1.5, "name": "a"},
+            {"kind": "square", "side": 2, "name": "b"},
+            {"kind": "circle", "radius": 2, "name": "c"}
+        ]);
+        for key in &[None, Some("kind")] {
+            let inferred = JSONSchema::new(&data).discriminator(*key).infer();
+            let variants = inferred["items"]["oneOf"].as_array().unwrap();
+            assert_eq!(variants.len(), 2);
+            assert_eq!(
+                variants[0]["properties"],
+                json!({
+                    "kind": {"type": "string", "const": "circle"},
+                    "name": {"type": "string"},
+                    "radius": {"type": "number"}
+                })
+            );
+            assert_eq!(
+                variants[1]["properties"],
+                json!({
+                    "kind": {"type": "string", "const": "square"},
+                    "name": {"type": "string"},
+                    "side": {"type": "integer"}
+                })
+            );
+            assert_eq!(variants[1]["required"].as_array().unwrap().len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_discriminator_not_detected() {
+        // `name` is different in every object, but all objects have the same shape
+        let data = json!([{"name": "a", "x": 1}, {"name": "b", "x": 2}]);
+        let inferred = JSONSchema::new(&data).discriminator(None).infer();
+        assert_eq!(inferred["items"]["type"], "object");
+        // Not every object has the tag
+        let data = json!([{"kind": "a", "x": 1}, {"y": 2}]);
+        let inferred = JSONSchema::new(&data).discriminator(Some("kind")).infer();
+        assert!(inferred["items"].get("oneOf").is_none());
+    }
+
+    #[test]
+    fn test_discriminator_draft4() {
+        let data = json!([{"kind": "a", "x": 1}, {"kind": "b", "y": 2}]);
+        let inferred = JSONSchema::new(&data)
+            .discriminator(None)
+            .draft(Draft::Draft4)
+            .infer();
+        assert_eq!(
+            inferred["items"]["oneOf"][0]["properties"]["kind"],
+            json!({"type": "string", "enum": ["a"]})
+        );
+    }
+
+    #[test]
+    fn test_string_length_bounds() {
+        let data = json!({"a": "foo", "b": ["x", "ÿÿÿÿ", "ab"]});
+        let inferred = JSONSchema::new(&data).string_length_bounds(true).infer();
+        assert_eq!(
+            inferred["properties"]["a"],
+            json!({"type": "string", "minLength": 3, "maxLength": 3})
+        );
+        assert_eq!(
+            inferred["properties"]["b"]["items"],
+            json!({"type": "string", "minLength": 1, "maxLength": 4})
+        );
+        let data = json!([{"a": ""}, {"a": "foobar"}]);
+        let inferred = JSONSchema::new(&data).string_length_bounds(true).infer();
+        assert_eq!(
+            inferred["items"]["properties"]["a"],
+            json!({"type": "string", "minLength": 0, "maxLength": 6})
+        );
+    }
+
+    #[test]
+    fn test_max_length_cap() {
+        let blob = "x".repeat(1000);
+        let data = json!(["foo", blob]);
+        let inferred = JSONSchema::new(&data)
+            .string_length_bounds(true)
+            .max_length_cap(255)
+            .infer();
+        assert_eq!(
+            inferred["items"],
+            json!({"type": "string", "minLength": 3, "maxLength": 255})
+        );
+        let data = json!(["foo", "barbaz"]);
+        let inferred = JSONSchema::new(&data)
+            .string_length_bounds(true)
+            .max_length_cap(255)
+            .infer();
+        assert_eq!(
+            inferred["items"],
+            json!({"type": "string", "minLength": 3, "maxLength": 6})
+        );
+        // A single string longer than the cap
+        let single = json!({"blob": blob});
+        let inferred = JSONSchema::new(&single)
+            .string_length_bounds(true)
+            .max_length_cap(255)
+            .infer();
+        assert_eq!(
+            inferred["properties"]["blob"],
+            json!({"type": "string", "minLength": 255, "maxLength": 255})
+        );
+        // No bounds without `string_length_bounds`
+        let inferred = JSONSchema::new(&data).max_length_cap(255).infer();
+        assert_eq!(inferred["items"], json!({"type": "string"}));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_format_name_mapper() {
+        let data = json!({"created": "2018-11-13T20:20:39+00:00", "day": "2018-11-13"});
+        let inferred = JSONSchema::new(&data)
+            .format_name_mapper(|name| match name {
+                "date-time" => "dateTime".to_owned(),
+                _ => name.to_owned(),
+            })
+            .infer();
+        assert_eq!(
+            inferred["properties"]["created"],
+            json!({"type": "string", "format": "dateTime"})
+        );
+        assert_eq!(
+            inferred["properties"]["day"],
+            json!({"type": "string", "format": "date"})
+        );
+    }
+
+    #[test]
+    fn test_duration_draft() {
+        let data = json!(["P3Y6M4DT12H30M5S", "P1D"]);
+        assert_eq!(infer(&data)["items"], json!({"type": "string"}));
+        let inferred = JSONSchema::new(&data).draft(Draft::Draft201909).infer();
+        assert_eq!(
+            inferred["items"],
+            json!({"type": "string", "format": "duration"})
+        );
+        let inferred = JSONSchema::new(&data)
+            .enable_format(Format::Duration)
+            .infer();
+        assert_eq!(inferred["items"]["format"], "duration");
+        let data = json!("Paris");
+        let inferred = JSONSchema::new(&data).draft(Draft::Draft202012).infer();
+        assert!(inferred.get("format").is_none());
+    }
+
+    #[test]
+    fn test_annotate_provenance() {
+        let data = json!([{"a": 1}, {"b": "foo"}, {"a": 2, "b": null}]);
+        let inferred = JSONSchema::new(&data).annotate_provenance(true).infer();
+        let items = &inferred["items"];
+        assert_eq!(items["$comment"], "merged from 3 object schemas");
+        assert_eq!(items["properties"]["a"], json!({"type": "integer"}));
+        assert_eq!(items["properties"]["b"]["$comment"], "union of 2 schemas");
+        assert!(infer(&data)["items"].get("$comment").is_none());
+    }
+
+    #[test]
+    fn test_emit_required() {
+        let data = json!({
+            "a": {"b": {"c": 1}},
+            "d": [{"e": 1}, {"e": 2, "f": [{"g": null}]}]
+        });
+        let inferred = JSONSchema::new(&data).emit_required(false).infer();
+        let mut found = false;
+        walk::walk_mut(&mut inferred.clone(), &mut |schema| {
+            found |= schema.get("required").is_some()
+        });
+        assert!(!found);
+        assert_eq!(
+            inferred["properties"]["a"],
+            json!({
+                "type": "object",
+                "properties": {"b": {"type": "object", "properties": {"c": {"type": "integer"}}}}
+            })
+        );
+    }
+
+    #[test]
+    fn test_merged_single_type_property() {
+        let data = json!([{"a": "foo", "b": 1}, {"a": "bar"}]);
+        let inferred = infer(&data);
+        assert_eq!(
+            inferred["items"]["properties"]["a"],
+            json!({"type": "string"})
+        );
+        assert_eq!(
+            inferred["items"]["properties"]["b"],
+            json!({"type": "integer"})
+        );
+    }
+
+    #[test]
+    fn test_key_enum_threshold() {
+        let data = json!({
+            "mon": {"open": 9}, "tue": {"open": 9}, "wed": {"open": 10},
+            "thu": {"open": 9}, "fri": {"open": 8}, "sat": {"open": 11}, "sun": {"open": 12}
+        });
+        let inferred = JSONSchema::new(&data)
+            .map_threshold(5)
+            .key_enum_threshold(7)
+            .infer();
+        assert_eq!(
+            inferred["propertyNames"],
+            json!({"enum": ["fri", "mon", "sat", "sun", "thu", "tue", "wed"]})
+        );
+        assert_eq!(
+            inferred["additionalProperties"]["properties"]["open"]["type"],
+            "integer"
+        );
+        // Too many keys for an enum
+        let inferred = JSONSchema::new(&data)
+            .map_threshold(5)
+            .key_enum_threshold(6)
+            .infer();
+        assert!(inferred["propertyNames"].get("enum").is_none());
+    }
+
+    #[test]
+    fn test_extract_definitions() {
+        let data = json!({
+            "billing": {"street": "a", "city": "b"},
+            "shipping": {"street": "c", "city": "d"},
+            "name": {"first": "e"}
+        });
+        let inferred = JSONSchema::new(&data).extract_definitions(true).infer();
+        let definitions = inferred["definitions"].as_object().unwrap();
+        assert_eq!(definitions.len(), 1);
+        let (name, definition) = definitions.iter().next().unwrap();
+        assert_eq!(name, "object-03414279c5a24c84");
+        assert_eq!(definition["properties"]["city"], json!({"type": "string"}));
+        let reference = json!({ "$ref": format!("#/definitions/{}", name) });
+        assert_eq!(inferred["properties"]["billing"], reference);
+        assert_eq!(inferred["properties"]["shipping"], reference);
+        assert_eq!(inferred["properties"]["name"]["type"], "object");
+        // Names are stable
+        for _ in 0..5 {
+            assert_eq!(
+                JSONSchema::new(&data).extract_definitions(true).infer(),
+                inferred
+            );
+        }
+        let inferred = JSONSchema::new(&data)
+            .extract_definitions(true)
+            .draft(Draft::Draft202012)
+            .infer();
+        assert_eq!(inferred["$defs"].as_object().unwrap().len(), 1);
+        assert_eq!(
+            inferred["properties"]["billing"]["$ref"],
+            format!("#/$defs/{}", name)
+        );
+    }
+
+    #[test]
+    fn test_definitions_limit() {
+        let mut data = Map::new();
+        for idx in 0..MAX_DEFINITIONS + 10 {
+            let object = json!({ format!("key-{}", idx): 1 });
+            data.insert(format!("a-{}", idx), object.clone());
+            data.insert(format!("b-{}", idx), object);
+        }
+        // The most frequent object is extracted first
+        data.insert("c".into(), json!({"key-0": 1}));
+        let inferred = JSONSchema::new(&Value::Object(data))
+            .extract_definitions(true)
+            .infer();
+        assert_eq!(
+            inferred["definitions"].as_object().unwrap().len(),
+            MAX_DEFINITIONS
+        );
+        assert!(inferred["properties"]["c"]["$ref"].is_string());
+        let inline = inferred["properties"]
+            .as_object()
+            .unwrap()
+            .values()
+            .filter(|property| property["type"] == "object")
+            .count();
+        assert_eq!(inline, 20);
+    }
+
+    #[test]
+    fn test_extract_nested_definitions() {
+        let point = json!({"x": 1, "y": 2});
+        let data = json!({"a": {"from": point, "to": point}, "b": {"from": point, "to": point}});
+        let inferred = JSONSchema::new(&data).extract_definitions(true).infer();
+        let definitions = inferred["definitions"].as_object().unwrap();
+        assert_eq!(definitions.len(), 2);
+        let segment = &inferred["properties"]["a"]["$ref"];
+        let segment = &definitions[segment
+            .as_str()
+            .unwrap()
+            .trim_start_matches("#/definitions/")];
+        assert!(segment["properties"]["from"].get("$ref").is_some());
+    }
+
+    #[test]
+    fn test_infer_from_iter() {
+        let samples = || {
+            (0..5).map(|idx| {
+                if idx % 2 == 0 {
+                    json!({"id": idx, "name": format!("user-{}", idx), "tags": ["a"]})
+                } else {
+                    json!({"id": idx as f64 + 0.5, "tags": ["b", "c"]})
+                }
+            })
+        };
+        let collected: Vec<Value> = samples().collect();
+        let expected = infer_samples(&collected);
+        assert_eq!(
+            expected,
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "number"},
+                    "name": {"type": "string"},
+                    "tags": {"type": "array", "items": {"type": "string"}}
+                },
+                "required": ["id", "tags"],
+                "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+        assert_eq!(infer_from_iter(samples()), expected);
+    }
+
+    #[test]
+    fn test_assume_positive() {
+        let data = json!([{"a": 1, "b": 0.5, "c": 0}, {"a": 5, "b": 2.5, "c": 3}]);
+        let inferred = JSONSchema::new(&data).assume_positive(true).infer();
+        let properties = &inferred["items"]["properties"];
+        assert_eq!(
+            properties["a"],
+            json!({"type": "integer", "exclusiveMinimum": 0})
+        );
+        assert_eq!(
+            properties["b"],
+            json!({"type": "number", "exclusiveMinimum": 0})
+        );
+        assert_eq!(properties["c"], json!({"type": "integer"}));
+        let inferred = JSONSchema::new(&data)
+            .assume_positive(true)
+            .assume_unsigned(true)
+            .infer();
+        let properties = &inferred["items"]["properties"];
+        assert_eq!(
+            properties["a"],
+            json!({"type": "integer", "minimum": 0, "exclusiveMinimum": 0})
+        );
+        assert_eq!(properties["c"], json!({"type": "integer", "minimum": 0}));
+    }
+
+    #[test]
+    fn test_assume_positive_draft4() {
+        let data = json!([{"a": 1, "c": 0}, {"a": 5, "c": 3}]);
+        let inferred = JSONSchema::new(&data)
+            .assume_positive(true)
+            .assume_unsigned(true)
+            .draft(Draft::Draft4)
+            .infer();
+        let properties = &inferred["items"]["properties"];
+        assert_eq!(
+            properties["a"],
+            json!({"type": "integer", "minimum": 0, "exclusiveMinimum": true})
+        );
+        assert_eq!(properties["c"], json!({"type": "integer", "minimum": 0}));
+        let data = json!([{"a": 1, "c": 0}, {"a": -5, "c": 3}]);
+        let inferred = JSONSchema::new(&data)
+            .assume_positive(true)
+            .draft(Draft::Draft4)
+            .infer();
+        assert_eq!(
+            inferred["items"]["properties"]["a"],
+            json!({"type": "integer"})
+        );
+    }
+
+    #[test]
+    fn test_detect_embedded_json() {
+        let data = json!({"payload": "{\"a\": 1}", "name": "{not json", "plain": "foo"});
+        let inferred = JSONSchema::new(&data)
+            .detect_embedded_json(true)
+            .draft(Draft::Draft202012)
+            .infer();
+        assert_eq!(
+            inferred["properties"]["payload"],
+            json!({
+                "type": "string",
+                "contentMediaType": "application/json",
+                "contentSchema": {
+                    "type": "object",
+                    "properties": {"a": {"type": "integer"}},
+                    "required": ["a"]
+                }
+            })
+        );
+        assert_eq!(inferred["properties"]["name"], json!({"type": "string"}));
+        assert_eq!(inferred["properties"]["plain"], json!({"type": "string"}));
+        let inferred = JSONSchema::new(&data).detect_embedded_json(true).infer();
+        assert_eq!(
+            inferred["properties"]["payload"],
+            json!({"type": "string", "contentMediaType": "application/json"})
+        );
+        assert_eq!(
+            infer(&data)["properties"]["payload"],
+            json!({"type": "string"})
+        );
+    }
+
+    #[test]
+    fn test_with_config() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "draft": "draft202012",
+                "formats": ["date", "duration"],
+                "object_merge_strategy": "compose",
+                "enum_threshold": 3
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.map_threshold, None);
+        let data = json!([{"a": "P1D"}, {"b": "2020-01-01"}]);
+        let inferred = JSONSchema::with_config(&data, config).infer();
+        assert_eq!(inferred["$schema"], Draft::Draft202012.uri());
+        let branches = inferred["items"]["anyOf"].as_array().unwrap();
+        assert_eq!(branches.len(), 2);
+        assert!(branches.iter().any(|branch| branch["properties"]["a"]
+            == json!({"type": "string", "format": "duration", "enum": ["P1D"]})));
+        // The same as the builder
+        let built = JSONSchema::new(&data)
+            .draft(Draft::Draft202012)
+            .formats(&[Format::Date, Format::Duration])
+            .object_merge_strategy(ObjectMergeStrategy::Compose)
+            .enum_threshold(3)
+            .infer();
+        assert_eq!(inferred, built);
+        assert!(serde_json::from_str::<Config>(r#"{"unknown": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_case_insensitive_keys() {
+        let data = json!([{"Email": "a@example.com", "id": 1}, {"email": null, "ID": 2}]);
+        let inferred = JSONSchema::new(&data).case_insensitive_keys(true).infer();
+        let properties = inferred["items"]["properties"].as_object().unwrap();
+        assert_eq!(properties.len(), 2);
+        assert_eq!(properties["Email"]["anyOf"].as_array().unwrap().len(), 2);
+        assert_eq!(properties["id"], json!({"type": "integer"}));
+        assert_eq!(inferred["items"]["required"], json!(["Email", "id"]));
+        // The first seen casing is kept regardless of the order of schema hashes
+        let case_insensitive =
+            |data: &Value| JSONSchema::new(data).case_insensitive_keys(true).infer();
+        let reversed = json!([{"email": null, "ID": 2}, {"Email": "a@example.com", "id": 1}]);
+        assert_eq!(
+            case_insensitive(&reversed)["items"]["required"],
+            json!(["ID", "email"])
+        );
+        let nested = json!([{"user": {"Name": "a"}}, {"user": {"name": null}}]);
+        let user = &case_insensitive(&nested)["items"]["properties"]["user"];
+        assert_eq!(user["required"], json!(["Name"]));
+        let inferred = infer(&data);
+        assert_eq!(
+            inferred["items"]["properties"],
+            json!({
+                "Email": {"type": "string"},
+                "email": {"type": "null"},
+                "ID": {"type": "integer"},
+                "id": {"type": "integer"}
+            })
+        );
+        assert!(inferred["items"].get("required").is_none());
+    }
+
+    #[test]
+    fn test_detect_recursion() {
+        let tree = json!({
+            "name": "root",
+            "children": [{"name": "a", "children": [{"name": "b"}]}, {"name": "c"}]
+        });
+        let inferred = JSONSchema::new(&tree).detect_recursion(true).infer();
+        assert_eq!(
+            inferred,
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "children": {"type": "array", "items": {"$ref": "#"}}
+                },
+                "required": ["name"],
+                "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+        let data = json!({"tree": tree, "id": 1});
+        let inferred = JSONSchema::new(&data).detect_recursion(true).infer();
+        let reference = inferred["properties"]["tree"]["$ref"].as_str().unwrap();
+        let name = reference.trim_start_matches("#/definitions/");
+        assert_eq!(
+            inferred["definitions"][name]["properties"]["children"]["items"]["$ref"],
+            reference
+        );
+        assert_eq!(inferred["properties"]["id"], json!({"type": "integer"}));
+        // Without recursion, the nesting follows the data
+        let inferred = infer(&tree);
+        assert_eq!(
+            inferred["properties"]["children"]["items"]["properties"]["children"]["items"],
+            json!({"type": "object", "properties": {"name": {"type": "string"}}, "required": ["name"]})
+        );
+    }
+
+    #[test]
+    fn test_ignore_paths() {
+        let data = json!({
+            "metadata": {"raw": {"free": ["form", 1]}, "size": 1},
+            "items": [{"id": 1, "raw": "x"}, {"id": 2, "raw": {"a": null}}],
+            "a/b": 5
+        });
+        let inferred = JSONSchema::new(&data)
+            .ignore_paths(&["/metadata/raw", "/items/*/raw", "/a~1b"])
+            .detect_defaults(true)
+            .infer();
+        assert_eq!(
+            inferred["properties"]["metadata"]["properties"]["raw"],
+            json!({})
+        );
+        assert_eq!(
+            inferred["properties"]["metadata"]["properties"]["size"],
+            json!({"type": "integer", "default": 1})
+        );
+        assert_eq!(
+            inferred["properties"]["items"]["items"]["properties"]["raw"],
+            json!({})
+        );
+        assert_eq!(
+            inferred["properties"]["items"]["items"]["properties"]["id"]["type"],
+            "integer"
+        );
+        assert_eq!(inferred["properties"]["a/b"], json!({}));
+    }
+
+    #[test]
+    fn test_pointer_matches() {
+        assert!(pointer_matches("/a/b", "/a/b"));
+        assert!(pointer_matches("/a/*/c", "/a/0/c"));
+        assert!(pointer_matches("", ""));
+        assert!(!pointer_matches("/a", "/a/b"));
+        assert!(!pointer_matches("/a/*", "/a"));
+        assert!(!pointer_matches("/a/b", "/a/c"));
+    }
+
+    #[test]
+    fn test_samples_of_mixed_types_order() {
+        let samples = vec![
+            json!({"a": 1}),
+            json!([1, 2]),
+            Value::Null,
+            json!(1),
+            json!("a"),
+            Value::Null,
+            json!(true),
+            json!(2.5),
+            json!({"b": null}),
         ];
-        assert_json(&cases);
+        let collected = infer_samples(&samples);
+        assert_eq!(collected["anyOf"].as_array().unwrap().len(), 6);
+        assert_eq!(infer_from_iter(samples.clone()), collected);
+        assert_eq!(
+            infer_from_iter(vec![Value::Null, Value::Null]),
+            infer_samples(&[Value::Null, Value::Null])
+        );
+        assert_eq!(infer_from_iter(vec![]), infer_samples(&[]));
+    }
+
+    #[test]
+    fn test_samples_of_different_types() {
+        let samples = vec![json!({"a": 1}), json!([1, 2])];
+        let object =
+            json!({"type": "object", "properties": {"a": {"type": "integer"}}, "required": ["a"]});
+        let array = json!({"type": "array", "items": {"type": "integer"}});
+        for inferred in &[infer_samples(&samples), infer_from_iter(samples.clone())] {
+            assert_eq!(
+                inferred["$schema"],
+                "http://json-schema.org/draft-07/schema#"
+            );
+            let branches = inferred["anyOf"].as_array().unwrap();
+            assert_eq!(branches.len(), 2);
+            assert!(branches.contains(&object));
+            assert!(branches.contains(&array));
+        }
+    }
+
+    #[test]
+    fn test_idn_formats() {
+        let data = json!({
+            "ascii": "user@example.com",
+            "unicode": "user@bücher.de",
+            "host": "例子.广告"
+        });
+        let schema = JSONSchema::new(&data).formats(&[
+            Format::Email,
+            Format::IdnEmail,
+            Format::Hostname,
+            Format::IdnHostname,
+        ]);
+        assert_eq!(
+            schema.infer()["properties"],
+            json!({
+              "ascii": {"type": "string", "format": "email"},
+              "unicode": {"type": "string", "format": "idn-email"},
+              "host": {"type": "string", "format": "idn-hostname"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_strict() {
+        let data = json!({"values": [1, "foo", null], "nested": [{"a": 1}, {"a": true}]});
+        let error = JSONSchema::new(&data).strict(true).try_infer().unwrap_err();
+        let conflicts = match &error {
+            InferError::TypeConflict { conflicts } => conflicts,
+            _ => panic!("Unexpected error: {}", error),
+        };
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].pointer, "/nested/*/a");
+        assert_eq!(conflicts[1].pointer, "/values/*");
+        let mut types = conflicts[1].types.clone();
+        types.sort();
+        assert_eq!(types, ["integer", "null", "string"]);
+        assert!(error
+            .to_string()
+            .starts_with("Conflicting types: '/nested/*/a' is "));
+        // Consistent data and number unification are fine
+        let data = json!([{"a": 1}, {"a": 1.5, "b": "foo"}]);
+        let schema = JSONSchema::new(&data).strict(true).unify_int_float(true);
+        assert!(schema.try_infer().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Inference failed")]
+    fn test_strict_refine() {
+        // Refinement fails on conflicts as `try_infer` does, instead of ignoring the strict mode
+        let data = json!({"values": [1, "foo"]});
+        JSONSchema::new(&data).strict(true).refine(&json!({}));
+    }
+
+    #[test]
+    fn test_strict_hidden_unions() {
+        let conflicts = |inferrer: JSONSchema| match inferrer.strict(true).try_infer() {
+            Err(InferError::TypeConflict { conflicts }) => conflicts,
+            result => panic!("Unexpected result: {:?}", result),
+        };
+        let conflict = |pointer: &str, types: &[&str]| TypeConflict {
+            pointer: pointer.into(),
+            types: types.iter().map(|&type_| type_.into()).collect(),
+        };
+        // Collapsed to a list of types
+        let data = json!([1, "a", null]);
+        assert_eq!(
+            conflicts(JSONSchema::new(&data).max_union_branches(1)),
+            [conflict("/*", &["integer", "null", "string"])]
+        );
+        // Replaced by `{}`
+        let data = json!({"values": [1, "a", true]});
+        assert_eq!(
+            conflicts(JSONSchema::new(&data).array_items_fallback(Fallback::Permissive(1))),
+            [conflict("/values/*", &["boolean", "integer", "string"])]
+        );
+        // A rare type is pruned
+        let data = json!([1, 2, 3, 4, "a"]);
+        assert_eq!(
+            conflicts(JSONSchema::new(&data).item_type_threshold(0.5)),
+            [conflict("/*", &["integer", "string"])]
+        );
+        // Integers are joined with numbers, also in merged objects
+        let data = json!([{"a": 1}, {"a": 2.5}, [1, 2.5]]);
+        assert_eq!(
+            conflicts(JSONSchema::new(&data)),
+            [
+                conflict("/*", &["array", "object"]),
+                conflict("/*/*", &["integer", "number"]),
+                conflict("/*/a", &["integer", "number"])
+            ]
+        );
+        assert!(JSONSchema::new(&json!([1, 2.5]))
+            .strict(true)
+            .unify_int_float(true)
+            .try_infer()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_detect_bool_const() {
+        let data = json!([{"active": true, "flag": true}, {"active": true, "flag": false}]);
+        let schema = JSONSchema::new(&data).detect_bool_const(true).infer();
+        assert_eq!(
+            schema["items"]["properties"],
+            json!({
+              "active": {"type": "boolean", "const": true},
+              "flag": {"type": "boolean"}
+            })
+        );
+        let schema = JSONSchema::new(&data)
+            .detect_bool_const(true)
+            .draft(Draft::Draft4)
+            .infer();
+        assert_eq!(
+            schema["items"]["properties"]["active"],
+            json!({"type": "boolean", "enum": [true]})
+        );
+        assert_eq!(
+            infer(&data)["items"]["properties"]["active"],
+            json!({"type": "boolean"})
+        );
+    }
+
+    #[test]
+    fn test_item_type_threshold() {
+        let mut data = vec![json!(null)];
+        data.extend((0..99).map(|idx| json!(idx)));
+        let data = Value::Array(data);
+        let schema = JSONSchema::new(&data).item_type_threshold(0.05);
+        assert_eq!(schema.infer()["items"], json!({"type": "integer"}));
+        let schema = JSONSchema::new(&data).item_type_threshold(0.01);
+        assert!(schema.infer()["items"]["anyOf"].is_array());
+        // Equally common types are kept
+        let data = json!([1, "foo"]);
+        let schema = JSONSchema::new(&data).item_type_threshold(0.9);
+        assert!(schema.infer()["items"]["anyOf"].is_array());
+    }
+
+    #[test]
+    fn test_homogeneous_items() {
+        for data in &[
+            Value::Array((0..1000).map(|idx| json!(idx)).collect()),
+            json!([1.5, 2.5]),
+            json!([null, null]),
+            json!([true, false]),
+            json!([1, 2, 2.5]),
+            json!([1, 2, "foo"]),
+            json!([[1], [2]]),
+        ] {
+            let mut general = JSONSchema::new(data);
+            general.cache = false;
+            assert_eq!(JSONSchema::new(data).infer(), general.infer(), "{}", data);
+        }
+        let data = json!(["a", "b"]);
+        let mut general = JSONSchema::new(&data).detect_format(false);
+        general.cache = false;
+        assert_eq!(
+            JSONSchema::new(&data).detect_format(false).infer(),
+            general.infer()
+        );
+        for data in &[
+            json!([true, true]),
+            json!([false, true]),
+            json!({"flags": [true, true]}),
+        ] {
+            let mut general = JSONSchema::new(data).detect_bool_const(true);
+            general.cache = false;
+            assert_eq!(
+                JSONSchema::new(data).detect_bool_const(true).infer(),
+                general.infer(),
+                "{}",
+                data
+            );
+        }
+        let data = json!({"same": [1, 2], "mixed": [1, "a"], "empty": []});
+        let permissive = |cache| {
+            let mut schema = JSONSchema::new(&data).array_items_fallback(Fallback::Permissive(0));
+            schema.cache = cache;
+            schema.infer()
+        };
+        assert_eq!(permissive(true), permissive(false));
+        assert_eq!(permissive(true)["properties"]["same"]["items"], json!({}));
+    }
+
+    #[test]
+    fn test_merge_nested_arrays() {
+        let data = json!([{"a": [1, 2]}, {"a": ["foo"]}, {"a": [true, "bar"]}]);
+        let property = &infer(&data)["items"]["properties"]["a"];
+        assert_eq!(property["type"], "array");
+        let mut branches = property["items"]["anyOf"].as_array().unwrap().clone();
+        branches.sort_by_key(|branch| branch["type"].as_str().unwrap().to_owned());
+        assert_eq!(
+            branches,
+            [
+                json!({"type": "boolean"}),
+                json!({"type": "integer"}),
+                json!({"type": "string"})
+            ]
+        );
+        // Objects inside the arrays are merged too
+        let data = json!([{"a": [{"b": 1}]}, {"a": [{"c": "foo"}]}]);
+        assert_eq!(
+            infer(&data)["items"]["properties"]["a"]["items"],
+            json!({
+              "type": "object",
+              "properties": {"b": {"type": "integer"}, "c": {"type": "string"}}
+            })
+        );
+    }
+
+    #[test]
+    fn test_sample_size() {
+        let data = Value::Array(
+            (0..1000)
+                .map(|idx| {
+                    if idx % 2 == 0 {
+                        json!(idx)
+                    } else {
+                        json!({"a": idx})
+                    }
+                })
+                .collect(),
+        );
+        let schema = JSONSchema::new(&data).sample_size(10).examples(3);
+        let first = schema.infer();
+        assert_eq!(
+            first,
+            JSONSchema::new(&data).sample_size(10).examples(3).infer()
+        );
+        assert_ne!(first, JSONSchema::new(&data).examples(3).infer());
+        // Arrays that are not larger than the sample size are not sampled
+        let data = json!([1, "foo", null]);
+        assert_eq!(JSONSchema::new(&data).sample_size(3).infer(), infer(&data));
+        // Sampling no items would describe an empty array
+        assert_eq!(JSONSchema::new(&data).sample_size(0).infer(), infer(&data));
+    }
+
+    #[test]
+    fn test_seed() {
+        let data = Value::Array((0..1000).map(|idx| json!({ "a": idx })).collect());
+        let infer_with_seed = |seed| {
+            let schema = JSONSchema::new(&data).sample_size(5).examples(5).seed(seed);
+            serde_json::to_string(&schema.infer()).unwrap()
+        };
+        assert_eq!(infer_with_seed(42), infer_with_seed(42));
+        assert_ne!(infer_with_seed(42), infer_with_seed(43));
+    }
+
+    #[test]
+    fn test_non_object_root() {
+        let data = json!([1, 2]);
+        let schema = JSONSchema::new(&data).hook(|schema, context| {
+            if context.pointer().is_empty() {
+                *schema = Value::Bool(true);
+            }
+        });
+        assert_eq!(
+            schema.infer(),
+            json!({"allOf": [true], "$schema": "http://json-schema.org/draft-07/schema#"})
+        );
+        // `$schema` is only at the root for array roots
+        let schema = infer_samples(&[json!([1]), json!({"a": 1})]);
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        for branch in schema["anyOf"].as_array().unwrap() {
+            assert!(branch.get("$schema").is_none());
+        }
+    }
+
+    #[test]
+    fn test_optional_annotation() {
+        let data = json!([
+            {"a": 1, "b": [{"c": 1}, {"c": 2, "d": true}]},
+            {"a": 2, "b": [{"c": 3}], "e": null}
+        ]);
+        let schema = JSONSchema::new(&data).optional_annotation(true).infer();
+        let items = &schema["items"];
+        assert_eq!(items["required"], json!(["a", "b"]));
+        assert!(items["properties"]["a"].get("x-optional").is_none());
+        assert_eq!(items["properties"]["e"]["x-optional"], true);
+        assert_eq!(
+            items["properties"]["b"]["items"],
+            json!({
+              "type": "object",
+              "properties": {"c": {"type": "integer"}, "d": {"type": "boolean", "x-optional": true}},
+              "required": ["c"]
+            })
+        );
+        // The annotations match `required`, which can be omitted
+        let schema = JSONSchema::new(&data)
+            .optional_annotation(true)
+            .emit_required(false)
+            .infer();
+        let mut expected = items.clone();
+        walk::walk_mut(&mut expected, &mut |schema| {
+            if let Value::Object(map) = schema {
+                map.remove("required");
+            }
+        });
+        assert_eq!(schema["items"], expected);
+    }
+
+    #[test]
+    fn test_unusual_characters() {
+        let data = json!({"control": "\u{0}\u{1b}[0m\t", "emoji": "\u{1f600}\u{10ffff}", "bom": "\u{feff}{}"});
+        let schema = JSONSchema::new(&data)
+            .formats(Format::ALL)
+            .detect_embedded_json(true)
+            .string_length_bounds(true)
+            .infer();
+        assert_eq!(
+            schema["properties"],
+            json!({
+              "control": {"type": "string", "minLength": 6, "maxLength": 6},
+              "emoji": {"type": "string", "minLength": 2, "maxLength": 2},
+              "bom": {"type": "string", "minLength": 3, "maxLength": 3}
+            })
+        );
+    }
+
+    #[test]
+    fn test_minimal() {
+        let data = json!({"a": {}, "b": [{}], "c": {"d": [1]}, "e": 1});
+        let full = JSONSchema::new(&data).ignore_paths(&["/c/d/*"]).infer();
+        assert_eq!(
+            full["properties"]["a"],
+            json!({"type": "object", "properties": {}, "required": []})
+        );
+        assert_eq!(
+            full["properties"]["c"]["properties"]["d"],
+            json!({"type": "array", "items": {}})
+        );
+        let minimal = JSONSchema::new(&data)
+            .ignore_paths(&["/c/d/*"])
+            .minimal(true)
+            .infer();
+        assert_eq!(
+            minimal,
+            json!({
+              "type": "object",
+              "properties": {
+                "a": {"type": "object"},
+                "b": {"type": "array", "items": {"type": "object"}},
+                "c": {
+                  "type": "object",
+                  "properties": {"d": {"type": "array"}},
+                  "required": ["d"]
+                },
+                "e": {"type": "integer"}
+              },
+              "required": ["a", "b", "c", "e"],
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+    }
+
+    #[test]
+    fn test_mark_write_only() {
+        let data = json!({"login": "foo", "password": "secret", "nested": {"password": "bar"}});
+        let schema = JSONSchema::new(&data)
+            .mark_write_only(|name| name == "password")
+            .infer();
+        let properties = &schema["properties"];
+        assert_eq!(
+            properties["password"],
+            json!({"type": "string", "writeOnly": true})
+        );
+        assert_eq!(properties["login"], json!({"type": "string"}));
+        assert_eq!(
+            properties["nested"]["properties"]["password"]["writeOnly"],
+            true
+        );
+        let schema = JSONSchema::new(&data)
+            .mark_write_only(|name| name == "password")
+            .draft(Draft::Draft6)
+            .infer();
+        assert!(schema["properties"]["password"].get("writeOnly").is_none());
+        assert!(infer(&data)["properties"]["password"]
+            .get("writeOnly")
+            .is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_simplify_unions() {
+        let data = json!(["2020-01-01", "2021-02-03", "foo", 1]);
+        let branches = infer(&data)["items"]["anyOf"].as_array().unwrap().clone();
+        assert_eq!(branches.len(), 2);
+        assert!(branches.contains(&json!({"type": "string"})));
+        let data = json!({"a": ["2020-01-01", "2018-11-13T20:20:39+00:00", "foo"]});
+        assert_eq!(
+            infer(&data)["properties"]["a"]["items"],
+            json!({"type": "string"})
+        );
+        // Branches that agree on the format are kept as is
+        let data = json!(["2020-01-01", "2021-02-03", 1]);
+        let branches = infer(&data)["items"]["anyOf"].as_array().unwrap().clone();
+        assert!(branches.contains(&json!({"type": "string", "format": "date"})));
+        let data = json!(["2020-01-01", "foo"]);
+        let schema = JSONSchema::new(&data).simplify_unions(false).infer();
+        assert_eq!(schema["items"]["anyOf"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_tuple_arrays() {
+        let data = json!([1, "foo"]);
+        let schema = JSONSchema::new(&data).tuple_arrays(true);
+        assert_eq!(
+            schema.infer(),
+            json!({
+              "type": "array",
+              "items": [{"type": "integer"}, {"type": "string"}],
+              "additionalItems": false,
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+        let schema = JSONSchema::new(&data)
+            .tuple_arrays(true)
+            .draft(Draft::Draft202012);
+        assert_eq!(
+            schema.infer()["prefixItems"],
+            json!([{"type": "integer"}, {"type": "string"}])
+        );
+    }
+
+    #[test]
+    fn test_set_semantics() {
+        let data = json!({"tags": ["a", "b"], "values": [1, 1]});
+        let schema = JSONSchema::new(&data)
+            .tuple_arrays(true)
+            .set_semantics(true)
+            .infer();
+        assert_eq!(
+            schema["properties"],
+            json!({
+              "tags": {"type": "array", "items": {"type": "string"}, "uniqueItems": true},
+              "values": {"type": "array", "items": {"type": "integer"}}
+            })
+        );
+        // Merged sets stay unique
+        let data = json!([{"tags": ["a"]}, {"tags": [1, 2]}]);
+        let schema = JSONSchema::new(&data).set_semantics(true).infer();
+        let tags = &schema["items"]["properties"]["tags"];
+        assert_eq!(tags["uniqueItems"], true);
+        assert!(tags["items"]["anyOf"].is_array());
+    }
+
+    #[test]
+    fn test_array_length_bounds() {
+        let data = json!([{"a": [1]}, {"a": [1, 2, 3]}, {"a": ["foo", "bar"]}]);
+        let inferred = JSONSchema::new(&data).array_length_bounds(true).infer();
+        let property = &inferred["items"]["properties"]["a"];
+        assert_eq!(property["minItems"], 1);
+        assert_eq!(property["maxItems"], 3);
+        assert!(property["items"]["anyOf"].is_array());
+        assert!(infer(&data)["items"]["properties"]["a"]
+            .get("maxItems")
+            .is_none());
+    }
+
+    #[test]
+    fn test_max_items_cap() {
+        let data = json!({"small": [1, 2], "large": vec![0; 100_000]});
+        let inferred = JSONSchema::new(&data)
+            .array_length_bounds(true)
+            .max_items_cap(1000)
+            .infer();
+        assert_eq!(
+            inferred["properties"],
+            json!({
+              "small": {"type": "array", "items": {"type": "integer"}, "minItems": 2, "maxItems": 2},
+              "large": {"type": "array", "items": {"type": "integer"}, "minItems": 100_000, "maxItems": 1000}
+            })
+        );
+    }
+
+    #[test]
+    fn test_union_order() {
+        let data = json!([true, "a", 1, "b", null, "c", 2]);
+        let types = |order| {
+            let schema = JSONSchema::new(&data).union_order(order).infer();
+            schema["items"]["anyOf"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|branch| branch["type"].as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            types(UnionOrder::TypeName),
+            ["boolean", "integer", "null", "string"]
+        );
+        assert_eq!(
+            types(UnionOrder::Frequency),
+            ["string", "integer", "boolean", "null"]
+        );
+        assert_eq!(
+            types(UnionOrder::FirstSeen),
+            ["boolean", "string", "integer", "null"]
+        );
+        let data = json!([{"a": 1}, {"a": "foo", "b": 1}, {"a": "bar", "c": 1}]);
+        let schema = JSONSchema::new(&data)
+            .union_order(UnionOrder::Frequency)
+            .infer();
+        assert_eq!(
+            schema["items"]["properties"]["a"]["anyOf"],
+            json!([{"type": "string"}, {"type": "integer"}])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_infer_ndjson_union() {
+        let input = "{\"id\": 1, \"name\": \"foo\"}\n\n[true]\n\"bar\"\n";
+        let schema = infer_ndjson_union(input.as_bytes()).unwrap();
+        let branches = schema["anyOf"].as_array().unwrap();
+        assert_eq!(branches.len(), 3);
+        for expected in &[
+            json!({
+                "type": "object",
+                "properties": {"id": {"type": "integer"}, "name": {"type": "string"}},
+                "required": ["id", "name"]
+            }),
+            json!({"type": "array", "items": {"type": "boolean"}}),
+            json!({"type": "string"}),
+        ] {
+            assert!(branches.contains(expected), "{} is missing", expected);
+        }
+        let input = concat!(
+            "{\"type\": \"login\", \"user\": \"foo\"}\n",
+            "{\"type\": \"click\", \"x\": 1, \"y\": 2}\n",
+            "{\"type\": \"login\", \"user\": \"bar\"}\n",
+            "{\"type\": \"click\", \"x\": 3, \"y\": 4}\n",
+        );
+        let schema = infer_ndjson_union(input.as_bytes()).unwrap();
+        assert_eq!(schema["oneOf"].as_array().map(Vec::len), Some(2));
+        assert!(matches!(
+            infer_ndjson_union("{}\n{\n".as_bytes()),
+            Err(InferError::InvalidInput { message }) if message.starts_with("line 2:")
+        ));
+    }
+
+    #[test]
+    fn test_forbidden_types() {
+        let data = json!({"a": [1, 2, null], "b": null});
+        let schema = JSONSchema::new(&data).forbidden_types(&["null"]);
+        let error = schema.try_infer().unwrap_err();
+        assert_eq!(
+            error,
+            InferError::ForbiddenType {
+                pointer: "/a/2".into(),
+                type_name: "null".into()
+            }
+        );
+        assert_eq!(error.to_string(), "Forbidden type null at '/a/2'");
+        let schema = JSONSchema::new(&data)
+            .forbidden_types(&["null"])
+            .ignore_paths(&["/a", "/b"]);
+        assert!(schema.try_infer().is_ok());
+        let data = json!([1, 2, 3]);
+        assert!(JSONSchema::new(&data)
+            .forbidden_types(&["number"])
+            .try_infer()
+            .is_ok());
+        assert!(JSONSchema::new(&data)
+            .forbidden_types(&["integer"])
+            .try_infer()
+            .is_err());
+    }
+
+    #[test]
+    fn test_email_validation() {
+        let data = json!("a@b");
+        let infer = |strictness| {
+            let mut schema = JSONSchema::new(&data)
+                .formats(&[Format::Email])
+                .email_validation(strictness)
+                .infer();
+            schema["format"].take()
+        };
+        assert_eq!(infer(EmailStrictness::Lenient), json!("email"));
+        assert_eq!(infer(EmailStrictness::Strict), Value::Null);
+    }
+
+    #[test]
+    fn test_openapi() {
+        let data = json!([{"a": 1}, {"a": null}]);
+        let schema = JSONSchema::new(&data).openapi(OpenApiVersion::V3_0).infer();
+        assert_eq!(
+            schema,
+            json!({
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {"a": {"type": "integer", "nullable": true}},
+                    "required": ["a"]
+                }
+            })
+        );
+        let schema = JSONSchema::new(&data).openapi(OpenApiVersion::V3_1).infer();
+        assert_eq!(
+            schema["items"]["properties"]["a"]["anyOf"]
+                .as_array()
+                .map(Vec::len),
+            Some(2)
+        );
+        assert!(schema.get("$schema").is_none());
+    }
+
+    #[test]
+    fn test_required_max_depth() {
+        let data = json!({"a": {"b": {"c": 1}}, "d": [{"e": 1}]});
+        let schema = JSONSchema::new(&data).required_max_depth(1).infer();
+        assert_eq!(schema["required"], json!(["a", "d"]));
+        assert_eq!(schema["properties"]["a"]["required"], json!(["b"]));
+        assert!(schema["properties"]["a"]["properties"]["b"]
+            .get("required")
+            .is_none());
+        assert_eq!(schema["properties"]["d"]["items"]["required"], json!(["e"]));
+        let data = json!([{"a": {"b": 1}}]);
+        let schema = JSONSchema::new(&data).required_max_depth(0).infer();
+        assert_eq!(schema["items"]["required"], json!(["a"]));
+        assert!(schema["items"]["properties"]["a"].get("required").is_none());
+    }
+
+    #[test]
+    fn test_length_unit() {
+        // "é" as "e" with a combining accent, and an emoji outside the Basic Multilingual Plane
+        let data = json!(["e\u{301}", "😀"]);
+        let lengths = |unit| {
+            let schema = JSONSchema::new(&data)
+                .string_length_bounds(true)
+                .length_unit(unit)
+                .infer();
+            (
+                schema["items"]["minLength"].clone(),
+                schema["items"]["maxLength"].clone(),
+            )
+        };
+        assert_eq!(lengths(LengthUnit::Chars), (json!(1), json!(2)));
+        assert_eq!(lengths(LengthUnit::Utf16), (json!(2), json!(2)));
+        assert_eq!(lengths(LengthUnit::Bytes), (json!(3), json!(4)));
+        #[cfg(feature = "std")]
+        assert_eq!(lengths(LengthUnit::Graphemes), (json!(1), json!(1)));
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn test_array_complex() {
-        let cases = [
-            (
-                json!([{"a": 1}, {"a": 2}]),
-                json!({
-                  "type": "array",
-                  "items": {
+    fn test_length_unit_graphemes() {
+        // A flag, a family joined with ZWJ, a Hangul syllable from jamo and CRLF
+        let data = json!([
+            "🇺🇦",
+            "👨\u{200d}👩\u{200d}👧",
+            "\u{1100}\u{1161}\u{11a8}",
+            "a\r\nb"
+        ]);
+        let schema = JSONSchema::new(&data)
+            .string_length_bounds(true)
+            .length_unit(LengthUnit::Graphemes)
+            .infer();
+        assert_eq!(schema["items"]["minLength"], json!(1));
+        assert_eq!(schema["items"]["maxLength"], json!(3));
+    }
+
+    #[test]
+    fn test_flatten_single_branch() {
+        let data = json!({"a": 1, "b": true});
+        let infer = |simplify_unions| {
+            JSONSchema::new(&data)
+                .hook(|schema, context| {
+                    if context.pointer() == "/a" {
+                        *schema = json!({"anyOf": [schema.take()], "$comment": "single"});
+                    } else if context.pointer() == "/b" {
+                        *schema = json!({"oneOf": [true]});
+                    }
+                })
+                .simplify_unions(simplify_unions)
+                .infer()
+        };
+        let schema = infer(true);
+        assert_eq!(
+            schema["properties"],
+            json!({"a": {"type": "integer", "$comment": "single"}, "b": true})
+        );
+        let schema = infer(false);
+        assert_eq!(schema["properties"]["b"], json!({"oneOf": [true]}));
+        let mut schema = json!({"anyOf": [{"type": "integer"}], "type": "number"});
+        let expected = schema.clone();
+        flatten_single_branch(&mut schema);
+        assert_eq!(schema, expected);
+    }
+
+    #[test]
+    fn test_merged_additional_properties() {
+        let data = json!([{"a": 1}, {"b": "foo"}]);
+        let infer = |additional| {
+            let mut schema = JSONSchema::new(&data)
+                .merged_additional_properties(additional)
+                .infer();
+            schema["items"]["additionalProperties"].take()
+        };
+        assert_eq!(infer(AdditionalProperties::Allow), Value::Null);
+        assert_eq!(infer(AdditionalProperties::Deny), json!(false));
+        assert_eq!(
+            infer(AdditionalProperties::Schema(json!({"type": "string"}))),
+            json!({"type": "string"})
+        );
+        // A single object is not merged
+        let data = json!({"a": {"b": 1}});
+        let schema = JSONSchema::new(&data)
+            .merged_additional_properties(AdditionalProperties::Deny)
+            .infer();
+        assert!(schema.get("additionalProperties").is_none());
+        assert!(schema["properties"]["a"]
+            .get("additionalProperties")
+            .is_none());
+    }
+
+    #[test]
+    fn test_detect_byte_arrays() {
+        let data = json!({"bytes": [0, 137, 80, 255], "numbers": [1, 300], "signed": [-1, 2]});
+        let schema = JSONSchema::new(&data).detect_byte_arrays(true).infer();
+        assert_eq!(
+            schema["properties"]["bytes"],
+            json!({
+                "type": "array",
+                "items": {"type": "integer", "minimum": 0, "maximum": 255},
+                "x-binary": true
+            })
+        );
+        for name in &["numbers", "signed"] {
+            assert_eq!(
+                schema["properties"][name],
+                json!({"type": "array", "items": {"type": "integer"}})
+            );
+        }
+        assert!(infer(&data)["properties"]["bytes"]
+            .get("x-binary")
+            .is_none());
+        let data = json!([{"data": [1, 2]}, {"data": [3]}, {"data": [1000]}]);
+        let schema = JSONSchema::new(&data).detect_byte_arrays(true).infer();
+        assert_eq!(
+            schema["items"]["properties"]["data"],
+            json!({"type": "array", "items": {"type": "integer"}})
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_only_formats() {
+        let data = json!({"date": "2020-01-01", "time": "2018-11-13T20:20:39+00:00", "n": "42"});
+        let schema = JSONSchema::new(&data)
+            .only_formats(&["date-time", "unknown"])
+            .infer();
+        assert_eq!(
+            schema["properties"],
+            json!({
+                "date": {"type": "string"},
+                "n": {"type": "string"},
+                "time": {"type": "string", "format": "date-time"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_emit_property_names() {
+        let data = json!([{"user_id": 1, "name": "foo"}, {"created_at": "bar"}]);
+        let schema = JSONSchema::new(&data).emit_property_names(true).infer();
+        assert_eq!(
+            schema["items"]["propertyNames"],
+            json!({"pattern": "^[a-z][a-z0-9]*(_[a-z0-9]+)*$", "maxLength": 10})
+        );
+        let data = json!({"userId": 1, "nested": {"ID": 1, "API_KEY": "foo"}});
+        let schema = JSONSchema::new(&data).emit_property_names(true).infer();
+        assert_eq!(schema["propertyNames"]["pattern"], "^[a-z][a-zA-Z0-9]*$");
+        assert_eq!(
+            schema["properties"]["nested"]["propertyNames"]["pattern"],
+            "^[A-Z][A-Z0-9]*(_[A-Z0-9]+)*$"
+        );
+        let data = json!({"user_id": 1, "userName": "foo", "Other Key": true});
+        let schema = JSONSchema::new(&data).emit_property_names(true).infer();
+        assert!(schema.get("propertyNames").is_none());
+        assert!(infer(&json!({"a": 1})).get("propertyNames").is_none());
+    }
+
+    #[test]
+    fn test_empty_array() {
+        assert_eq!(
+            infer(&json!([]))["items"],
+            json!({"type": "object", "properties": {}})
+        );
+    }
+
+    #[test]
+    fn test_explicit_null_and_absence() {
+        // An explicit `null` makes the property nullable, it stays required
+        let schema = infer(&json!([{"a": null}, {"a": 1}]));
+        assert_eq!(
+            schema["items"],
+            json!({
+                "type": "object",
+                "properties": {"a": {"anyOf": [{"type": "null"}, {"type": "integer"}]}},
+                "required": ["a"]
+            })
+        );
+        // An absent property is optional, but not nullable
+        let schema = infer(&json!([{"a": 1}, {}]));
+        assert_eq!(
+            schema["items"],
+            json!({"type": "object", "properties": {"a": {"type": "integer"}}})
+        );
+        // The same holds for nested objects, which are merged instead of joined via `anyOf`
+        let data = json!([{"b": {"a": 1}}, {"b": {}}, {"b": null}]);
+        assert_eq!(
+            infer(&data)["items"]["properties"]["b"],
+            json!({"anyOf": [
+                {"type": "null"},
+                {"type": "object", "properties": {"a": {"type": "integer"}}}
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_merged_tagged_unions() {
+        let data = json!([
+            {"shapes": [{"kind": "circle", "radius": 1}, {"kind": "square", "side": 2}]},
+            {"shapes": [{"kind": "square", "side": 3.5}]}
+        ]);
+        let inferred = JSONSchema::new(&data).discriminator(Some("kind")).infer();
+        assert_eq!(
+            inferred["items"]["properties"]["shapes"]["items"],
+            json!({"oneOf": [
+                {
                     "type": "object",
                     "properties": {
-                      "a": {"type": "integer"}
+                        "kind": {"type": "string", "const": "circle"},
+                        "radius": {"type": "integer"}
                     },
-                    "required": ["a"]
-                  },
-                  "$schema": "http://json-schema.org/draft-07/schema#"
-                }),
-            ),
-            (
-                json!([{"a": 1}, {"a": null}, {"a": 2}]),
-                json!({
-                  "type": "array",
-                  "items": {
+                    "required": ["kind", "radius"]
+                },
+                {
                     "type": "object",
-                    "required": ["a"],
                     "properties": {
-                      "a": {
-                        "anyOf": [
-                          {"type": "null"},
-                          {"type": "integer"},
-                        ]
-                      }
-                    }
-                  },
-                  "$schema": "http://json-schema.org/draft-07/schema#"
-                }),
+                        "kind": {"type": "string", "const": "square"},
+                        "side": {"type": "number"}
+                    },
+                    "required": ["kind", "side"]
+                }
+            ]})
+        );
+        // A single tag value is described as an object with a constant tag
+        let data = json!([{"kind": "circle", "radius": 1}]);
+        let inferred = JSONSchema::new(&data).discriminator(Some("kind")).infer();
+        assert_eq!(
+            inferred["items"]["properties"]["kind"],
+            json!({"type": "string", "const": "circle"})
+        );
+    }
+
+    #[test]
+    fn test_infer_properties() {
+        let data = json!({"id": 1, "user": {"name": "foo"}, "scores": [1.5, null]});
+        let properties = infer_properties(&data);
+        assert_eq!(properties.len(), 3);
+        assert_eq!(properties["id"], json!({"type": "integer"}));
+        assert_eq!(
+            properties["user"],
+            json!({
+                "type": "object",
+                "properties": {"name": {"type": "string"}},
+                "required": ["name"]
+            })
+        );
+        assert_eq!(
+            properties["scores"],
+            json!({"type": "array", "items": {"anyOf": [{"type": "null"}, {"type": "number"}]}})
+        );
+        assert_eq!(
+            Value::Object(properties.into_iter().collect()),
+            infer(&data)["properties"]
+        );
+        assert!(infer_properties(&json!([1])).is_empty());
+    }
+
+    #[test]
+    fn test_type_array_order() {
+        for (order, expected) in &[
+            (
+                TypeArrayOrder::Alphabetical,
+                json!(["integer", "null", "string"]),
             ),
-            // Proper required detection.
             (
-                json!([{"a": 1}, {"b": "test"}]),
-                json!({
-                  "type": "array",
-                  "items": {
-                    "type": "object",
-                    "properties": {
-                      "a": {"type": "integer"},
-                      "b": {"type": "string"}
-                    }
-                  },
-                  "$schema": "http://json-schema.org/draft-07/schema#"
-                }),
+                TypeArrayOrder::NullLast,
+                json!(["integer", "string", "null"]),
+            ),
+        ] {
+            let inferred = JSONSchema::new(&json!([null, "a", 1]))
+                .type_array_order(*order)
+                .infer();
+            assert_eq!(inferred["items"], json!({ "type": expected }));
+        }
+        // Unions with branches that have other keywords are kept
+        let data = json!([null, "a", 1, {"a": "b"}]);
+        let inferred = JSONSchema::new(&data)
+            .type_array_order(TypeArrayOrder::NullLast)
+            .infer();
+        assert_eq!(inferred["items"]["anyOf"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_format_merge() {
+        let dates = json!([{"a": "2020-01-01"}, {"a": "2020-02-02"}]);
+        let mixed = json!([{"a": "2020-01-01"}, {"a": "foo"}]);
+        let formats = json!([{"a": "2020-01-01"}, {"a": "2020-01-01T00:00:00Z"}]);
+        for strategy in &[FormatMerge::Intersect, FormatMerge::Union] {
+            let property = |data: &Value| {
+                JSONSchema::new(data).format_merge(*strategy).infer()["items"]["properties"]["a"]
+                    .clone()
+            };
+            assert_eq!(
+                property(&dates),
+                json!({"type": "string", "format": "date"})
+            );
+            assert_eq!(property(&mixed), json!({"type": "string"}));
+            let expected = if *strategy == FormatMerge::Union {
+                json!({"type": "string", "anyOf": [{"format": "date"}, {"format": "date-time"}]})
+            } else {
+                json!({"type": "string"})
+            };
+            assert_eq!(property(&formats), expected);
+            // Empty strings don't prevent keeping the format
+            let with_empty = json!([{"a": "2020-01-01"}, {"a": ""}, {"a": "2020-02-02"}]);
+            assert_eq!(
+                property(&with_empty),
+                json!({"type": "string", "format": "date"})
+            );
+            let items = JSONSchema::new(&json!(["2020-01-01", ""]))
+                .format_merge(*strategy)
+                .infer();
+            assert_eq!(items["items"], json!({"type": "string", "format": "date"}));
+            let with_empty = json!([{"a": "2020-01-01"}, {"a": ""}, {"a": "foo"}]);
+            assert_eq!(property(&with_empty), json!({"type": "string"}));
+            let with_empty = json!([{"a": "2020-01-01"}, {"a": "2020-01-01T00:00:00Z"}, {"a": ""}]);
+            assert_eq!(property(&with_empty), expected);
+        }
+        // Only empty strings, alone or joined
+        let empty = json!({"a": "", "b": ["", ""]});
+        assert_eq!(
+            infer(&empty)["properties"],
+            json!({"a": {"type": "string"}, "b": {"type": "array", "items": {"type": "string"}}})
+        );
+    }
+
+    #[test]
+    fn test_annotate_in_place() {
+        let mut data = json!({
+            "name": "foo",
+            "score": 1.5,
+            "user": {"id": 1, "$types": "replaced"},
+            "items": [{"ok": true}, null]
+        });
+        annotate_in_place(&mut data);
+        assert_eq!(
+            data,
+            json!({
+                "name": "foo",
+                "score": 1.5,
+                "user": {"id": 1, "$types": {"id": {"type": "integer"}}},
+                "items": [{"ok": true, "$types": {"ok": {"type": "boolean"}}}, null],
+                "$types": {
+                    "items": {"type": "array"},
+                    "name": {"type": "string"},
+                    "score": {"type": "number"},
+                    "user": {"type": "object"}
+                }
+            })
+        );
+        let mut scalar = json!(1);
+        annotate_in_place(&mut scalar);
+        assert_eq!(scalar, json!(1));
+    }
+
+    #[test]
+    fn test_format_priority() {
+        let data = json!("mailto:user@example.com");
+        let detect = |priority: &[&str]| {
+            JSONSchema::new(&data)
+                .formats(&[Format::Integer, Format::Uri, Format::Email])
+                .email_validation(EmailStrictness::Lenient)
+                .format_priority(priority)
+                .infer()["format"]
+                .clone()
+        };
+        assert_eq!(detect(&[]), "uri");
+        assert_eq!(detect(&["email", "unknown"]), "email");
+        assert_eq!(detect(&["uri", "email"]), "uri");
+    }
+
+    #[test]
+    fn test_format_settings_order() {
+        let data = json!(["P1D", "mailto:user@example.com"]);
+        let detect = |inferrer: JSONSchema| {
+            let inferred = inferrer
+                .email_validation(EmailStrictness::Lenient)
+                .simplify_unions(false)
+                .infer();
+            let branches = inferred["items"]["anyOf"].as_array().unwrap().clone();
+            let mut formats: Vec<Value> = branches
+                .iter()
+                .map(|branch| branch["format"].clone())
+                .collect();
+            formats.sort_by(compare_scalars);
+            formats
+        };
+        let expected = vec![json!("duration"), json!("email")];
+        // The draft enables `duration` whether formats are selected before or after it
+        assert_eq!(
+            detect(
+                JSONSchema::new(&data)
+                    .draft(Draft::Draft201909)
+                    .formats(&[Format::Uri, Format::Email])
+                    .format_priority(&["email"])
+            ),
+            expected
+        );
+        assert_eq!(
+            detect(
+                JSONSchema::new(&data)
+                    .format_priority(&["email"])
+                    .formats(&[Format::Uri, Format::Email])
+                    .draft(Draft::Draft201909)
             ),
+            expected
+        );
+        let config: Config = serde_json::from_value(json!({
+            "draft": "draft201909",
+            "formats": ["uri", "email"],
+            "format_priority": ["email"]
+        }))
+        .expect("Invalid config");
+        assert_eq!(detect(JSONSchema::with_config(&data, config)), expected);
+    }
+
+    #[test]
+    fn test_max_union_branches() {
+        let data = json!([{"a": 1}, {"b": 2}, {"c": 3}]);
+        let inferrer = JSONSchema::new(&data).object_merge_strategy(ObjectMergeStrategy::Compose);
+        assert_eq!(
+            inferrer.infer()["items"]["anyOf"].as_array().unwrap().len(),
+            3
+        );
+        let inferred = inferrer.max_union_branches(2).infer();
+        assert_eq!(inferred["items"], json!({"type": "object"}));
+        let data = json!([1, "a", null]);
+        let inferred = JSONSchema::new(&data).max_union_branches(2).infer();
+        assert_eq!(inferred["items"]["type"].as_array().unwrap().len(), 3);
+        let inferred = JSONSchema::new(&data).max_union_branches(3).infer();
+        assert_eq!(inferred["items"]["anyOf"].as_array().unwrap().len(), 3);
+        // Ignored values have no type
+        let inferred = JSONSchema::new(&data)
+            .ignore_paths(&["/0"])
+            .max_union_branches(2)
+            .infer();
+        assert_eq!(inferred["items"], json!({}));
+    }
+
+    #[test]
+    fn test_array_items_enum() {
+        let data = json!(["red", "green", "red", "blue"]);
+        assert_eq!(
+            JSONSchema::new(&data).enum_threshold(3).infer()["items"],
+            json!({"type": "string", "enum": ["blue", "green", "red"]})
+        );
+        assert_eq!(
+            JSONSchema::new(&data).enum_threshold(2).infer()["items"],
+            json!({"type": "string"})
+        );
+        let data = json!([1, 2, 1, null]);
+        assert_eq!(
+            JSONSchema::new(&data).enum_threshold(3).infer()["items"],
+            json!({"anyOf": [{"type": "integer", "enum": [1, 2]}, {"type": "null"}]})
+        );
+    }
+
+    #[test]
+    fn test_root_example() {
+        let data = json!({"id": 1, "tags": ["a"]});
+        let inferred = JSONSchema::new(&data).root_example(true).infer();
+        assert_eq!(inferred["examples"], json!([data]));
+        assert!(inferred["properties"]["id"].get("examples").is_none());
+        assert!(infer(&data).get("examples").is_none());
+        let samples = [
+            json!({"a": 1}),
+            json!({"a": 1}),
+            json!({"a": 2}),
+            json!({"a": 3}),
         ];
-        assert_json(&cases);
+        assert_eq!(
+            infer_samples_with_examples(&samples, 2)["examples"],
+            json!([{"a": 1}, {"a": 2}])
+        );
+        assert!(infer_samples_with_examples(&samples, 0)
+            .get("examples")
+            .is_none());
     }
 
     #[test]
-    fn test_infer_via_schema() {
-        let data = json!(null);
-        let schema = JSONSchema::new(&data);
+    fn test_number_tokens() {
+        let data: Value = serde_json::from_str("[5, 5.0, 1e2]").unwrap();
+        let types: Vec<Value> = data
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| infer(item)["type"].clone())
+            .collect();
         assert_eq!(
-            schema.infer(),
-            json!({"type": "null", "$schema": "http://json-schema.org/draft-07/schema#"})
+            types,
+            vec![json!("integer"), json!("number"), json!("number")]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary_precision")]
+    fn test_arbitrary_precision() {
+        let data: Value =
+            serde_json::from_str("[123456789012345678901234567890, 1e400, 5.00]").unwrap();
+        let types: Vec<Value> = data
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| infer(item)["type"].clone())
+            .collect();
+        assert_eq!(
+            types,
+            vec![json!("integer"), json!("number"), json!("number")]
+        );
+        // Shortcuts and type checks agree with the inferred types
+        let data: Value = serde_json::from_str("[1, 1e400]").unwrap();
+        assert_eq!(infer(&data)["items"], json!({"type": "number"}));
+        let data: Value = serde_json::from_str("[1e400, 2.5]").unwrap();
+        assert!(JSONSchema::new(&data)
+            .forbidden_types(&["integer"])
+            .try_infer()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_emit_generator_info() {
+        let data = json!({"a": 1});
+        let info = json!({"name": "infers-jsonschema", "version": env!("CARGO_PKG_VERSION")});
+        let inferred = JSONSchema::new(&data).emit_generator_info(true).infer();
+        assert_eq!(inferred["x-generated-by"], info);
+        assert_eq!(inferred["$schema"], Draft::Draft7.uri());
+        let inferred = JSONSchema::new(&data)
+            .emit_generator_info(true)
+            .generated_at("2020-01-01T00:00:00Z")
+            .infer();
+        assert_eq!(
+            inferred["x-generated-by"]["timestamp"],
+            "2020-01-01T00:00:00Z"
+        );
+        let inferred = JSONSchema::new(&data)
+            .emit_generator_info(false)
+            .generated_at("2020-01-01T00:00:00Z")
+            .infer();
+        assert!(inferred.get("x-generated-by").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_format_and_enum() {
+        let expected =
+            json!({"type": "string", "format": "date", "enum": ["2020-01-01", "2020-01-02"]});
+        let data = json!([{"d": "2020-01-02"}, {"d": "2020-01-01"}, {"d": "2020-01-02"}]);
+        let inferred = JSONSchema::new(&data).enum_threshold(3).infer();
+        assert_eq!(inferred["items"]["properties"]["d"], expected);
+        let data = json!(["2020-01-02", "2020-01-01", "2020-01-02"]);
+        let inferred = JSONSchema::new(&data).enum_threshold(3).infer();
+        assert_eq!(inferred["items"], expected);
+        // Formats are reconciled before values are joined
+        let data = json!(["2020-01-01", "2020-01-01T00:00:00Z"]);
+        let inferred = JSONSchema::new(&data)
+            .enum_threshold(3)
+            .format_merge(FormatMerge::Union)
+            .infer();
+        assert_eq!(
+            inferred["items"],
+            json!({
+                "type": "string",
+                "anyOf": [{"format": "date"}, {"format": "date-time"}],
+                "enum": ["2020-01-01", "2020-01-01T00:00:00Z"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_union_examples() {
+        let data = json!([{"id": "a"}, {"id": 1}, {"id": "b"}, {"id": null}]);
+        let inferred = JSONSchema::new(&data).examples(1).infer();
+        let branches = inferred["items"]["properties"]["id"]["anyOf"]
+            .as_array()
+            .unwrap();
+        assert_eq!(branches.len(), 3);
+        for branch in branches {
+            match branch["type"].as_str().unwrap() {
+                "string" => assert!(["a", "b"].contains(&branch["examples"][0].as_str().unwrap())),
+                "integer" => assert_eq!(branch["examples"], json!([1])),
+                _ => assert!(branch.get("examples").is_none()),
+            }
+        }
+        let data = json!(["a", 1, "b", 2]);
+        let inferred = JSONSchema::new(&data).examples(2).infer();
+        assert_eq!(
+            inferred["items"],
+            json!({"anyOf": [
+                {"type": "integer", "examples": [1, 2]},
+                {"type": "string", "examples": ["a", "b"]}
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_canonical_hash() {
+        let mut first = Map::new();
+        first.insert("type".into(), json!("object"));
+        first.insert(
+            "properties".into(),
+            json!({"a": {"type": "integer"}, "b": {}}),
+        );
+        let mut second = Map::new();
+        second.insert(
+            "properties".into(),
+            json!({"b": {}, "a": {"type": "integer"}}),
+        );
+        second.insert("type".into(), json!("object"));
+        let (first, second) = (Value::Object(first), Value::Object(second));
+        assert_eq!(canonical_hash(&first), canonical_hash(&second));
+        assert_ne!(
+            canonical_hash(&first),
+            canonical_hash(&json!({"type": "object", "properties": {"a": {"type": "number"}}}))
+        );
+        assert_ne!(canonical_hash(&json!(1)), canonical_hash(&json!(1.5)));
+    }
+
+    #[test]
+    fn test_insert_distinct() {
+        let mut schemas = BTreeMap::new();
+        // Distinct schemas with colliding hashes are both kept
+        insert_distinct(&mut schemas, 1, json!({"type": "integer"}));
+        insert_distinct(&mut schemas, 1, json!({"type": "string"}));
+        insert_distinct(&mut schemas, 1, json!({"type": "integer"}));
+        insert_distinct(&mut schemas, 2, json!({"type": "integer"}));
+        assert_eq!(
+            schemas.into_values().flatten().collect::<Vec<_>>(),
+            [
+                json!({"type": "integer"}),
+                json!({"type": "string"}),
+                json!({"type": "integer"})
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merged_property_numbers() {
+        let data = json!([{"a": 1, "b": 1}, {"a": 1.5, "b": "foo"}, {"a": 2, "b": 2.5}]);
+        let inferred = infer(&data);
+        assert_eq!(
+            inferred["items"]["properties"],
+            json!({
+                "a": {"type": "number"},
+                "b": {"anyOf": [{"type": "number"}, {"type": "string"}]}
+            })
+        );
+        let merged = merge_schemas(&[
+            &json!({"type": "object", "properties": {"a": {"type": "integer"}}}),
+            &json!({"type": "object", "properties": {"a": {"type": "number"}}}),
+        ])
+        .unwrap();
+        assert_eq!(merged["properties"]["a"], json!({"type": "number"}));
+    }
+
+    #[test]
+    fn test_require_composite_root() {
+        let data = json!(42);
+        let error = JSONSchema::new(&data)
+            .require_composite_root(true)
+            .try_infer()
+            .unwrap_err();
+        assert_eq!(
+            error,
+            InferError::ScalarRoot {
+                type_name: "integer".into()
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Expected an object or an array, got integer"
+        );
+        assert_eq!(
+            JSONSchema::new(&data)
+                .require_composite_root(false)
+                .try_infer()
+                .unwrap()["type"],
+            "integer"
+        );
+        for data in &[json!([1]), json!({"a": 1})] {
+            assert!(JSONSchema::new(data)
+                .require_composite_root(true)
+                .try_infer()
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_network_formats() {
+        let data = json!({
+            "subnet": "10.0.0.0/8",
+            "mac": "00:1A:2B:3C:4D:5E",
+            "address": "10.0.0.1",
+            "not_mac": "00:1A:2B:3C:4D"
+        });
+        assert_eq!(
+            JSONSchema::new(&data).infer()["properties"]["subnet"],
+            json!({"type": "string"})
+        );
+        let config: Config = serde_json::from_value(json!({"formats": ["x-cidr", "x-mac"]}))
+            .expect("Invalid config");
+        assert_eq!(
+            JSONSchema::with_config(&data, config).infer()["properties"],
+            json!({
+              "subnet": {"type": "string", "format": "x-cidr"},
+              "mac": {"type": "string", "format": "x-mac"},
+              "address": {"type": "string"},
+              "not_mac": {"type": "string"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_number_bounds() {
+        let data = json!({"scores": [12.34, 99.97, 50.5], "counts": [3, 7]});
+        let inferred = JSONSchema::new(&data).number_bounds(true).infer();
+        assert_eq!(
+            inferred["properties"]["scores"]["items"],
+            json!({"type": "number", "minimum": 12.34, "maximum": 99.97})
+        );
+        assert_eq!(
+            inferred["properties"]["counts"]["items"],
+            json!({"type": "integer", "minimum": 3, "maximum": 7})
+        );
+        let inferred = JSONSchema::new(&data)
+            .number_bounds(true)
+            .bounds_rounding(0)
+            .infer();
+        // Bounds are rounded outward and integers stay as they are
+        assert_eq!(
+            inferred["properties"]["scores"]["items"],
+            json!({"type": "number", "minimum": 12.0, "maximum": 100.0})
+        );
+        assert_eq!(
+            inferred["properties"]["counts"]["items"],
+            json!({"type": "integer", "minimum": 3, "maximum": 7})
+        );
+        let data = json!([{"a": -1.25}, {"a": 3.125}]);
+        let inferred = JSONSchema::new(&data)
+            .number_bounds(true)
+            .bounds_rounding(1)
+            .infer();
+        assert_eq!(
+            inferred["items"]["properties"]["a"],
+            json!({"type": "number", "minimum": -1.3, "maximum": 3.2})
+        );
+        // Values a unit in the last place away from a decimal stay within rounded bounds
+        for (value, places, minimum, maximum) in [
+            (900.6999999999999, 1, 900.6, 900.7),
+            (10.870000000000001, 2, 10.87, 10.88),
+        ] {
+            let data = json!([value]);
+            let inferred = JSONSchema::new(&data)
+                .number_bounds(true)
+                .bounds_rounding(places)
+                .infer();
+            assert_eq!(
+                inferred["items"],
+                json!({"type": "number", "minimum": minimum, "maximum": maximum})
+            );
+        }
+        let inferrer = JSONSchema::new(&Value::Null).bounds_rounding(2);
+        for idx in 1..20_000u64 {
+            let decimal = idx as f64 / 1000.0;
+            for value in [
+                f64::from_bits(decimal.to_bits() - 1),
+                decimal,
+                f64::from_bits(decimal.to_bits() + 1),
+            ] {
+                let (minimum, maximum) =
+                    inferrer.number_bounds_of(&Number::from_f64(value).unwrap());
+                assert!(minimum.as_f64().unwrap() <= value, "{}", value);
+                assert!(maximum.as_f64().unwrap() >= value, "{}", value);
+            }
+        }
+        // No bounds without `number_bounds`
+        let inferred = JSONSchema::new(&data).bounds_rounding(1).infer();
+        assert_eq!(
+            inferred["items"]["properties"]["a"],
+            json!({"type": "number"})
+        );
+    }
+
+    #[test]
+    fn test_array_items_fallback() {
+        let data = json!([1, "a", null, true, 1.5, [1], {"a": 1}]);
+        let inferred = JSONSchema::new(&data)
+            .array_items_fallback(Fallback::Permissive(3))
+            .infer();
+        assert_eq!(inferred["items"], json!({}));
+        // Arrays with fewer schemas are described as usual
+        let data = json!({"mixed": [1, "a", null, true, 1.5, [1], {"a": 1}], "ids": [1, "a"]});
+        let config: Config =
+            serde_json::from_value(json!({"array_items_fallback": {"permissive": 3}}))
+                .expect("Invalid config");
+        let inferred = JSONSchema::with_config(&data, config).infer();
+        assert_eq!(inferred["properties"]["mixed"]["items"], json!({}));
+        assert_eq!(
+            inferred["properties"]["ids"]["items"]["anyOf"]
+                .as_array()
+                .map(Vec::len),
+            Some(2)
+        );
+        // Integers are described as numbers, so there are 6 schemas
+        let inferred = JSONSchema::new(&data)
+            .array_items_fallback(Fallback::AnyOf)
+            .infer();
+        assert_eq!(
+            inferred["properties"]["mixed"]["items"]["anyOf"]
+                .as_array()
+                .map(Vec::len),
+            Some(6)
         );
     }
 }
@@ -0,0 +1,295 @@
+//! Merging of already inferred schemas.
+use alloc::{string::String, vec, vec::Vec};
+use serde_json::{Map, Number, Value};
+
+/// Keywords whose smallest value is kept
+const LOWER_BOUNDS: &[&str] = &["minimum", "minLength", "minItems", "minProperties"];
+/// Keywords whose largest value is kept
+const UPPER_BOUNDS: &[&str] = &["maximum", "maxLength", "maxItems", "maxProperties"];
+/// Keywords that don't restrict values, the value of the first schema is kept
+const ANNOTATIONS: &[&str] = &["$schema", "title", "description", "$comment", "default"];
+
+/// Merge two schemas into one that accepts values accepted by any of them.
+///
+/// Properties of object schemas are joined and only properties required by both schemas stay
+/// required, bounds are widened and schemas of different types are joined via `anyOf`.
+/// Keywords that the schemas disagree on, e.g. different formats, are dropped.
+///
+/// ```rust
+/// use infers_jsonschema::{infer, merge};
+/// use serde_json::json;
+///
+/// let first = infer(&json!({"a": 1, "b": "foo"}));
+/// let second = infer(&json!({"a": 1.5, "c": null}));
+/// assert_eq!(
+///     merge(&first, &second),
+///     json!({
+///         "type": "object",
+///         "properties": {
+///             "a": {"type": "number"},
+///             "b": {"type": "string"},
+///             "c": {"type": "null"}
+///         },
+///         "required": ["a"],
+///         "$schema": "http://json-schema.org/draft-07/schema#"
+///     })
+/// );
+/// ```
+pub fn merge(first: &Value, second: &Value) -> Value {
+    let (first_map, second_map) = match (first, second) {
+        (Value::Object(first_map), Value::Object(second_map)) => (first_map, second_map),
+        _ if first == second => return first.clone(),
+        // `true` or `{}` accept any value
+        (Value::Bool(true), _) | (_, Value::Bool(true)) => return Value::Bool(true),
+        (Value::Bool(false), other) | (other, Value::Bool(false)) => return other.clone(),
+        _ => return union(vec![first.clone(), second.clone()]),
+    };
+    if first_map.is_empty() || second_map.is_empty() {
+        return Value::Object(Map::new());
+    }
+    if first_map.contains_key("anyOf") || second_map.contains_key("anyOf") {
+        let mut branches = branches(first);
+        for branch in branches_of(second) {
+            add_branch(&mut branches, branch);
+        }
+        let mut result = if branches.len() == 1 {
+            branches.swap_remove(0)
+        } else {
+            union(branches)
+        };
+        copy_annotations(&mut result, first_map, second_map);
+        return result;
+    }
+    match (first_map.get("type"), second_map.get("type")) {
+        (Some(first_type), Some(second_type)) if first_type == second_type => {
+            Value::Object(merge_keywords(first_map, second_map))
+        }
+        (Some(Value::String(first_type)), Some(Value::String(second_type)))
+            if is_numeric(first_type) && is_numeric(second_type) =>
+        {
+            let mut result = merge_keywords(first_map, second_map);
+            result.insert("type".into(), Value::String("number".into()));
+            Value::Object(result)
+        }
+        (Some(_), Some(_)) => {
+            let mut result = union(vec![without_schema(first_map), without_schema(second_map)]);
+            copy_annotations(&mut result, first_map, second_map);
+            result
+        }
+        // No type restrictions - any value is accepted
+        _ => Value::Object(Map::new()),
+    }
+}
+
+fn is_numeric(type_: &str) -> bool {
+    type_ == "integer" || type_ == "number"
+}
+
+fn union(branches: Vec<Value>) -> Value {
+    let mut result = Map::new();
+    result.insert("anyOf".into(), Value::Array(branches));
+    Value::Object(result)
+}
+
+/// Branches of a union, or the schema itself without `$schema`
+fn branches(schema: &Value) -> Vec<Value> {
+    branches_of(schema).collect()
+}
+
+fn branches_of(schema: &Value) -> impl Iterator<Item = Value> + '_ {
+    let (branches, single) = match schema.get("anyOf").and_then(Value::as_array) {
+        Some(branches) => (branches.clone(), None),
+        None => (vec![], schema.as_object().map(without_schema)),
+    };
+    branches.into_iter().chain(single)
+}
+
+/// Merge the branch with a branch of the same type if there is one
+fn add_branch(branches: &mut Vec<Value>, branch: Value) {
+    let same_type = branches
+        .iter_mut()
+        .find(|known| known.get("type").is_some() && known["type"] == branch["type"]);
+    match same_type {
+        Some(known) => *known = merge(known, &branch),
+        None => {
+            if !branches.contains(&branch) {
+                branches.push(branch)
+            }
+        }
+    }
+}
+
+fn without_schema(map: &Map<String, Value>) -> Value {
+    let mut map = map.clone();
+    map.remove("$schema");
+    Value::Object(map)
+}
+
+/// Keep annotations from the roots of the merged schemas
+fn copy_annotations(result: &mut Value, first: &Map<String, Value>, second: &Map<String, Value>) {
+    if let Value::Object(result) = result {
+        for keyword in ANNOTATIONS {
+            if let Some(value) = first.get(*keyword).or_else(|| second.get(*keyword)) {
+                result.insert((*keyword).into(), value.clone());
+            }
+        }
+    }
+}
+
+/// Merge keywords of two schemas of the same type
+fn merge_keywords(first: &Map<String, Value>, second: &Map<String, Value>) -> Map<String, Value> {
+    let mut result = Map::new();
+    for (keyword, value) in first {
+        let other = second.get(keyword);
+        let keyword = keyword.as_str();
+        let merged = match (keyword, other) {
+            ("properties", Some(other)) => merge_properties(value, other),
+            ("required", Some(other)) => {
+                let required: Vec<Value> = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|name| other.as_array().is_some_and(|other| other.contains(name)))
+                    .cloned()
+                    .collect();
+                if required.is_empty() {
+                    None
+                } else {
+                    Some(Value::Array(required))
+                }
+            }
+            ("items" | "additionalProperties", Some(other)) => Some(merge(value, other)),
+            (_, Some(Value::Number(other))) if LOWER_BOUNDS.contains(&keyword) => {
+                Some(pick(value, other, |first, second| first <= second))
+            }
+            (_, Some(Value::Number(other))) if UPPER_BOUNDS.contains(&keyword) => {
+                Some(pick(value, other, |first, second| first >= second))
+            }
+            ("exclusiveMinimum", Some(Value::Number(other))) => {
+                Some(pick(value, other, |first, second| first <= second))
+            }
+            ("exclusiveMaximum", Some(Value::Number(other))) => {
+                Some(pick(value, other, |first, second| first >= second))
+            }
+            ("enum" | "examples", Some(Value::Array(other))) => value.as_array().map(|values| {
+                let mut values = values.clone();
+                for item in other {
+                    if !values.contains(item) {
+                        values.push(item.clone())
+                    }
+                }
+                Value::Array(values)
+            }),
+            ("const", Some(other)) if value != other => {
+                let values = vec![value.clone(), other.clone()];
+                result.insert("enum".into(), Value::Array(values));
+                None
+            }
+            _ if ANNOTATIONS.contains(&keyword) => Some(value.clone()),
+            // Keywords present in both schemas with the same value, e.g. `type`
+            (_, Some(other)) if value == other => Some(value.clone()),
+            // Examples don't restrict values
+            ("examples", None) => Some(value.clone()),
+            // Keywords that are absent in one of the schemas or differ don't restrict values
+            _ => None,
+        };
+        if let Some(merged) = merged {
+            result.insert(keyword.into(), merged);
+        }
+    }
+    for (keyword, value) in second {
+        if !first.contains_key(keyword)
+            && (ANNOTATIONS.contains(&keyword.as_str()) || keyword == "examples")
+        {
+            result.insert(keyword.clone(), value.clone());
+        }
+    }
+    result
+}
+
+/// Join properties, merging schemas of properties present in both
+fn merge_properties(first: &Value, second: &Value) -> Option<Value> {
+    let mut properties = first.as_object()?.clone();
+    for (name, schema) in second.as_object()? {
+        let merged = match properties.get(name) {
+            Some(known) => merge(known, schema),
+            None => schema.clone(),
+        };
+        properties.insert(name.clone(), merged);
+    }
+    Some(Value::Object(properties))
+}
+
+/// Pick one of two numeric keyword values
+fn pick(first: &Value, second: &Number, keep_first: fn(f64, f64) -> bool) -> Value {
+    match (first.as_f64(), second.as_f64()) {
+        (Some(left), Some(right)) if !keep_first(left, right) => Value::Number(second.clone()),
+        _ => first.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infer;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_objects() {
+        let first = json!({
+          "type": "object",
+          "properties": {"id": {"type": "integer"}, "name": {"type": "string", "maxLength": 3}},
+          "required": ["id", "name"]
+        });
+        let second = json!({
+          "type": "object",
+          "properties": {
+            "id": {"type": "string"},
+            "name": {"type": "string", "maxLength": 5},
+            "tags": {"type": "array", "items": {"type": "string"}}
+          },
+          "required": ["name", "tags"]
+        });
+        assert_eq!(
+            merge(&first, &second),
+            json!({
+              "type": "object",
+              "properties": {
+                "id": {"anyOf": [{"type": "integer"}, {"type": "string"}]},
+                "name": {"type": "string", "maxLength": 5},
+                "tags": {"type": "array", "items": {"type": "string"}}
+              },
+              "required": ["name"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_unions() {
+        let first = json!({"anyOf": [{"type": "integer", "minimum": 1}, {"type": "null"}]});
+        let second = json!({"anyOf": [{"type": "integer", "minimum": 0}, {"type": "boolean"}]});
+        assert_eq!(
+            merge(&first, &second),
+            json!({"anyOf": [
+              {"type": "integer", "minimum": 0},
+              {"type": "null"},
+              {"type": "boolean"}
+            ]})
+        );
+        assert_eq!(merge(&first, &json!({})), json!({}));
+    }
+
+    #[test]
+    fn test_merge_inferred() {
+        let first = infer(&json!([1, 2]));
+        let second = infer(&json!(["foo"]));
+        assert_eq!(
+            merge(&first, &second),
+            json!({
+              "type": "array",
+              "items": {"anyOf": [{"type": "integer"}, {"type": "string"}]},
+              "$schema": "http://json-schema.org/draft-07/schema#"
+            })
+        );
+    }
+}
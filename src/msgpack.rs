@@ -0,0 +1,304 @@
+//! Inference from MessagePack documents.
+use crate::{base64::base64, escape_token, InferError, JSONSchema};
+use serde_json::{Map, Number, Value};
+use std::collections::HashSet;
+use std::convert::TryInto;
+
+/// The maximum nesting of arrays and maps, as in `serde_json` and `serde_cbor`
+const RECURSION_LIMIT: usize = 128;
+
+/// Infer a schema for a MessagePack document with default settings.
+/// Binary data is described as strings with `contentEncoding: base64` and timestamps as
+/// `date-time` strings
+pub fn infer_msgpack(input: &[u8]) -> Result<Value, InferError> {
+    let mut decoder = Decoder {
+        input,
+        position: 0,
+        depth: 0,
+        bytes: HashSet::new(),
+    };
+    let converted = decoder.value(String::new())?;
+    if decoder.position != input.len() {
+        return Err(decoder.error("Trailing data"));
+    }
+    let bytes = decoder.bytes;
+    let schema = JSONSchema::new(&converted)
+        .hook(|schema, context| {
+            if bytes.contains(context.pointer()) {
+                schema["contentEncoding"] = Value::String("base64".into());
+            }
+        })
+        .try_infer();
+    schema
+}
+
+/// Converts MessagePack to JSON, collecting pointers to binary data
+struct Decoder<'a> {
+    input: &'a [u8],
+    position: usize,
+    /// The number of arrays and maps containing the current value
+    depth: usize,
+    bytes: HashSet<String>,
+}
+
+impl Decoder<'_> {
+    fn error(&self, message: &str) -> InferError {
+        InferError::InvalidInput {
+            message: format!("{} at byte {}", message, self.position),
+        }
+    }
+
+    fn take(&mut self, length: usize) -> Result<&[u8], InferError> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|&end| end <= self.input.len())
+            .ok_or_else(|| self.error("Unexpected end of input"))?;
+        let data = &self.input[self.position..end];
+        self.position = end;
+        Ok(data)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], InferError> {
+        Ok(self.take(N)?.try_into().expect("Length is checked"))
+    }
+
+    /// Length stored in the following `width` bytes
+    fn length(&mut self, width: usize) -> Result<usize, InferError> {
+        Ok(match width {
+            1 => self.array::<1>()?[0] as usize,
+            2 => u16::from_be_bytes(self.array()?) as usize,
+            _ => u32::from_be_bytes(self.array()?) as usize,
+        })
+    }
+
+    fn value(&mut self, pointer: String) -> Result<Value, InferError> {
+        let marker = self.array::<1>()?[0];
+        Ok(match marker {
+            0x00..=0x7f => Value::Number(marker.into()),
+            0x80..=0x8f => self.map((marker & 0x0f) as usize, pointer)?,
+            0x90..=0x9f => self.items((marker & 0x0f) as usize, pointer)?,
+            0xa0..=0xbf => self.string((marker & 0x1f) as usize)?,
+            0xc0 => Value::Null,
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xc4..=0xc6 => {
+                let length = self.length(1 << (marker - 0xc4))?;
+                let data = base64(self.take(length)?);
+                self.bytes.insert(pointer);
+                Value::String(data)
+            }
+            0xc7..=0xc9 => {
+                let length = self.length(1 << (marker - 0xc7))?;
+                self.extension(length, pointer)?
+            }
+            0xca => float(f32::from_be_bytes(self.array()?) as f64, pointer)?,
+            0xcb => float(f64::from_be_bytes(self.array()?), pointer)?,
+            0xcc => Value::Number(self.array::<1>()?[0].into()),
+            0xcd => Value::Number(u16::from_be_bytes(self.array()?).into()),
+            0xce => Value::Number(u32::from_be_bytes(self.array()?).into()),
+            0xcf => Value::Number(u64::from_be_bytes(self.array()?).into()),
+            0xd0 => Value::Number(i8::from_be_bytes(self.array()?).into()),
+            0xd1 => Value::Number(i16::from_be_bytes(self.array()?).into()),
+            0xd2 => Value::Number(i32::from_be_bytes(self.array()?).into()),
+            0xd3 => Value::Number(i64::from_be_bytes(self.array()?).into()),
+            0xd4..=0xd8 => self.extension(1 << (marker - 0xd4), pointer)?,
+            0xd9..=0xdb => {
+                let length = self.length(1 << (marker - 0xd9))?;
+                self.string(length)?
+            }
+            0xdc | 0xdd => {
+                let length = self.length(2 << (marker - 0xdc))?;
+                self.items(length, pointer)?
+            }
+            0xde | 0xdf => {
+                let length = self.length(2 << (marker - 0xde))?;
+                self.map(length, pointer)?
+            }
+            0xe0..=0xff => Value::Number((marker as i8).into()),
+            0xc1 => {
+                self.position -= 1;
+                return Err(self.error("Invalid marker"));
+            }
+        })
+    }
+
+    fn string(&mut self, length: usize) -> Result<Value, InferError> {
+        let data = self.take(length)?.to_vec();
+        String::from_utf8(data)
+            .map(Value::String)
+            .map_err(|_| self.error("Invalid UTF-8 string"))
+    }
+
+    /// Enter an array or a map, so deeply nested input fails instead of overflowing the stack
+    fn nest(&mut self) -> Result<(), InferError> {
+        self.depth += 1;
+        if self.depth > RECURSION_LIMIT {
+            return Err(self.error("Recursion limit exceeded"));
+        }
+        Ok(())
+    }
+
+    fn items(&mut self, length: usize, pointer: String) -> Result<Value, InferError> {
+        self.nest()?;
+        // The length may be bogus, so the capacity is capped by the remaining input
+        let mut items = Vec::with_capacity(length.min(self.input.len() - self.position));
+        for idx in 0..length {
+            items.push(self.value(format!("{}/{}", pointer, idx))?);
+        }
+        self.depth -= 1;
+        Ok(Value::Array(items))
+    }
+
+    fn map(&mut self, length: usize, pointer: String) -> Result<Value, InferError> {
+        self.nest()?;
+        let mut map = Map::new();
+        for _ in 0..length {
+            let key = match self.value(pointer.clone())? {
+                Value::String(key) => key,
+                Value::Number(key) => key.to_string(),
+                Value::Bool(key) => key.to_string(),
+                _ => {
+                    return Err(InferError::InvalidInput {
+                        message: format!("Unsupported map key at '{}'", pointer),
+                    })
+                }
+            };
+            let location = format!("{}/{}", pointer, escape_token(&key));
+            let value = self.value(location)?;
+            map.insert(key, value);
+        }
+        self.depth -= 1;
+        Ok(Value::Object(map))
+    }
+
+    /// Only the timestamp extension type is supported, as an RFC 3339 string
+    fn extension(&mut self, length: usize, pointer: String) -> Result<Value, InferError> {
+        let kind = self.array::<1>()?[0] as i8;
+        let data = self.take(length)?;
+        let (seconds, nanoseconds) = match (kind, data.len()) {
+            (-1, 4) => (
+                u32::from_be_bytes(data.try_into().expect("Length is checked")) as i64,
+                0,
+            ),
+            (-1, 8) => {
+                let value = u64::from_be_bytes(data.try_into().expect("Length is checked"));
+                ((value & 0x3_ffff_ffff) as i64, (value >> 34) as u32)
+            }
+            (-1, 12) => (
+                i64::from_be_bytes(data[4..].try_into().expect("Length is checked")),
+                u32::from_be_bytes(data[..4].try_into().expect("Length is checked")),
+            ),
+            _ => {
+                return Err(InferError::InvalidInput {
+                    message: format!("Unsupported value at '{}'", pointer),
+                })
+            }
+        };
+        chrono::DateTime::from_timestamp(seconds, nanoseconds)
+            .map(|timestamp| {
+                Value::String(timestamp.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+            })
+            .ok_or_else(|| InferError::InvalidInput {
+                message: format!("Timestamp out of range at '{}'", pointer),
+            })
+    }
+}
+
+fn float(number: f64, pointer: String) -> Result<Value, InferError> {
+    Number::from_f64(number)
+        .map(Value::Number)
+        .ok_or(InferError::NonFiniteNumber { pointer })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let document = json!({"id": 1, "name": "foo", "tags": ["a", "b"], "score": 1.5});
+        let mut encoded = vec![0x84];
+        encoded.extend_from_slice(b"\xa2id\x01");
+        encoded.extend_from_slice(b"\xa4name\xa3foo");
+        encoded.extend_from_slice(b"\xa4tags\x92\xa1a\xa1b");
+        encoded.extend_from_slice(b"\xa5score\xcb");
+        encoded.extend_from_slice(&1.5f64.to_be_bytes());
+        assert_eq!(infer_msgpack(&encoded), Ok(crate::infer(&document)));
+    }
+
+    #[test]
+    fn test_numbers() {
+        // fixint, uint16, negative fixint, int32 and float32
+        let encoded = b"\x95\x05\xcd\x01\x00\xff\xd2\xff\xff\xff\x00\xca\x3f\xc0\x00\x00";
+        assert_eq!(
+            infer_msgpack(encoded),
+            Ok(crate::infer(&json!([5, 256, -1, -256, 1.5])))
+        );
+    }
+
+    #[test]
+    fn test_bytes() {
+        let encoded = b"\x82\xa4data\xc4\x05hello\xa6chunks\x92\xc4\x01\x01\xc4\x00";
+        let schema = infer_msgpack(encoded).unwrap();
+        let bytes = json!({"type": "string", "contentEncoding": "base64"});
+        assert_eq!(schema["properties"]["data"], bytes);
+        assert_eq!(schema["properties"]["chunks"]["items"], bytes);
+    }
+
+    #[test]
+    fn test_timestamp() {
+        // 32-bit timestamp of 2020-01-01T00:00:00Z
+        let mut encoded = b"\x81\xa7created\xd6\xff".to_vec();
+        encoded.extend_from_slice(&1_577_836_800u32.to_be_bytes());
+        let schema = infer_msgpack(&encoded).unwrap();
+        assert_eq!(
+            schema["properties"]["created"],
+            json!({"type": "string", "format": "date-time"})
+        );
+    }
+
+    #[test]
+    fn test_non_finite() {
+        let mut encoded = b"\x92\x01\xcb".to_vec();
+        encoded.extend_from_slice(&f64::NAN.to_be_bytes());
+        assert_eq!(
+            infer_msgpack(&encoded),
+            Err(InferError::NonFiniteNumber {
+                pointer: "/1".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        let nested = |depth: usize, marker: u8| {
+            let mut encoded = vec![marker; depth];
+            encoded.push(0x01);
+            encoded
+        };
+        assert!(infer_msgpack(&nested(RECURSION_LIMIT, 0x91)).is_ok());
+        for input in [nested(200_000, 0x91), {
+            // Maps with the next map as the value of the `a` key
+            let mut encoded = b"\x81\xa1a".repeat(200_000);
+            encoded.push(0x01);
+            encoded
+        }] {
+            assert!(matches!(
+                infer_msgpack(&input),
+                Err(InferError::InvalidInput { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        for input in [&b"\xc1"[..], b"\x92\x01", b"\x01\x02", b"\xa3ab"] {
+            assert!(matches!(
+                infer_msgpack(input),
+                Err(InferError::InvalidInput { .. })
+            ));
+        }
+    }
+}
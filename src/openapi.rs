@@ -0,0 +1,173 @@
+//! Conversion of inferred schemas to the OpenAPI dialect.
+use crate::{walk, OpenApiVersion};
+use alloc::{string::String, vec, vec::Vec};
+use serde_json::{Map, Value};
+
+/// Rewrite keywords that OpenAPI 3.0 doesn't support. OpenAPI 3.1 uses JSON Schema 2020-12 as is
+pub(crate) fn to_openapi(schema: &mut Value, version: OpenApiVersion) {
+    if version == OpenApiVersion::V3_0 {
+        // Top-down, so `null` branches are removed before their parent's type is adjusted
+        walk::walk_top_down_mut(schema, &mut |schema| {
+            if let Value::Object(map) = schema {
+                remove_null_branches(map);
+                convert_type(map);
+                convert_keywords(map);
+            }
+            true
+        });
+    }
+}
+
+/// Replace `null` branches of `anyOf` with `nullable: true` on the remaining branches.
+/// A single remaining branch replaces the union
+fn remove_null_branches(map: &mut Map<String, Value>) {
+    let branches = match map.get_mut("anyOf") {
+        Some(Value::Array(branches)) => branches,
+        _ => return,
+    };
+    let count = branches.len();
+    branches.retain(|branch| !is_null(branch));
+    if branches.len() == count {
+        return;
+    }
+    let mut branches = match map.remove("anyOf") {
+        Some(Value::Array(branches)) => branches,
+        _ => return,
+    };
+    if branches.len() == 1 {
+        if let Value::Object(branch) = branches.swap_remove(0) {
+            map.extend(branch);
+        }
+        set_nullable(map);
+    } else {
+        for branch in &mut branches {
+            if let Value::Object(branch) = branch {
+                set_nullable(branch);
+            }
+        }
+        map.insert("anyOf".into(), Value::Array(branches));
+    }
+}
+
+/// Whether the schema only accepts `null`
+fn is_null(schema: &Value) -> bool {
+    schema.as_object().is_some_and(|map| {
+        map.len() == 1
+            && match &map["type"] {
+                Value::String(type_) => type_ == "null",
+                Value::Array(types) => types.len() == 1 && types[0] == "null",
+                _ => false,
+            }
+    })
+}
+
+/// Mark a schema with a type as nullable. A schema of the `null` type, which doesn't exist in
+/// OpenAPI 3.0, is converted by `convert_type` later
+fn set_nullable(map: &mut Map<String, Value>) {
+    if map.get("type").is_some_and(|type_| type_ != "null") {
+        map.insert("nullable".into(), Value::Bool(true));
+    }
+}
+
+/// OpenAPI 3.0 has neither the `null` type nor arrays of types
+fn convert_type(map: &mut Map<String, Value>) {
+    let mut types: Vec<Value> = match map.get("type") {
+        Some(Value::String(type_)) if type_ == "null" => vec![],
+        Some(Value::Array(types)) => types.clone(),
+        _ => return,
+    };
+    map.remove("type");
+    let count = types.len();
+    types.retain(|type_| type_ != "null");
+    let nullable = types.len() < count || count == 0;
+    match types.len() {
+        0 => {
+            map.insert("enum".into(), Value::Array(vec![Value::Null]));
+        }
+        1 => {
+            map.insert("type".into(), types.swap_remove(0));
+        }
+        _ => {
+            let branches = types
+                .into_iter()
+                .map(|type_| {
+                    let mut branch = Map::new();
+                    branch.insert("type".into(), type_);
+                    if nullable {
+                        branch.insert("nullable".into(), Value::Bool(true));
+                    }
+                    Value::Object(branch)
+                })
+                .collect();
+            map.insert("anyOf".into(), Value::Array(branches));
+            return;
+        }
+    }
+    if nullable {
+        map.insert("nullable".into(), Value::Bool(true));
+    }
+}
+
+/// Keywords with OpenAPI-specific counterparts
+fn convert_keywords(map: &mut Map<String, Value>) {
+    if let Some(Value::Array(mut examples)) = map.remove("examples") {
+        if !examples.is_empty() {
+            map.insert("example".into(), examples.swap_remove(0));
+        }
+    }
+    if map
+        .get("contentEncoding")
+        .is_some_and(|encoding| encoding == "base64")
+    {
+        map.remove("contentEncoding");
+        map.insert("format".into(), Value::String("byte".into()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_null_unions() {
+        let mut schema = json!({
+            "type": "object",
+            "properties": {
+                "a": {"anyOf": [{"type": "integer"}, {"type": "null"}, {"type": "string"}]},
+                "b": {"type": ["string", "null"]},
+                "c": {"type": "null"},
+                "d": {"type": ["boolean", "integer"]}
+            }
+        });
+        to_openapi(&mut schema, OpenApiVersion::V3_0);
+        assert_eq!(
+            schema["properties"],
+            json!({
+                "a": {"anyOf": [
+                    {"type": "integer", "nullable": true},
+                    {"type": "string", "nullable": true}
+                ]},
+                "b": {"type": "string", "nullable": true},
+                "c": {"enum": [null], "nullable": true},
+                "d": {"anyOf": [{"type": "boolean"}, {"type": "integer"}]}
+            })
+        );
+    }
+
+    #[test]
+    fn test_keywords() {
+        let mut schema = json!({
+            "type": "array",
+            "items": {"type": "string", "contentEncoding": "base64", "examples": ["AAE=", "Ag=="]}
+        });
+        let expected = schema.clone();
+        to_openapi(&mut schema, OpenApiVersion::V3_1);
+        assert_eq!(schema, expected);
+        to_openapi(&mut schema, OpenApiVersion::V3_0);
+        assert_eq!(
+            schema["items"],
+            json!({"type": "string", "format": "byte", "example": "AAE="})
+        );
+    }
+}
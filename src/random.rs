@@ -0,0 +1,55 @@
+//! Deterministic pseudo-random numbers for sampling.
+use alloc::collections::BTreeSet;
+
+/// The seed used unless another one is configured via `JSONSchema::seed`
+pub(crate) const DEFAULT_SEED: u64 = 0x5eed;
+
+/// SplitMix64 generator. It is not suitable for cryptography, but it is fast and gives the same
+/// sequence for the same seed on all platforms
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.0;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+
+    /// A number in `0..bound`
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        ((u128::from(self.next_u64()) * bound as u128) >> 64) as usize
+    }
+}
+
+/// Choose `size` distinct indices in `0..length` with Floyd's algorithm, in ascending order
+pub(crate) fn sample_indices(length: usize, size: usize, rng: &mut Rng) -> BTreeSet<usize> {
+    let mut indices = BTreeSet::new();
+    for upper in length - size..length {
+        let idx = rng.below(upper + 1);
+        if !indices.insert(idx) {
+            indices.insert(upper);
+        }
+    }
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_indices() {
+        let first = sample_indices(1000, 10, &mut Rng::new(1));
+        assert_eq!(first.len(), 10);
+        assert!(first.iter().all(|&idx| idx < 1000));
+        assert_eq!(first, sample_indices(1000, 10, &mut Rng::new(1)));
+        assert_ne!(first, sample_indices(1000, 10, &mut Rng::new(2)));
+        assert_eq!(sample_indices(5, 5, &mut Rng::new(1)).len(), 5);
+    }
+}
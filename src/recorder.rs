@@ -0,0 +1,91 @@
+//! Bookkeeping during inference: type conflicts for the strict mode and statistics.
+use crate::{stats::Collector, TypeConflict};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::ops::DerefMut;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "std")]
+type Lock<T> = std::sync::Mutex<T>;
+/// Without `std`, items are inferred sequentially
+#[cfg(not(feature = "std"))]
+type Lock<T> = core::cell::RefCell<T>;
+
+#[cfg(feature = "std")]
+fn lock<T>(lock: &Lock<T>) -> impl DerefMut<Target = T> + '_ {
+    // Records are only extended, so they are consistent even after a panic in a hook
+    lock.lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[cfg(not(feature = "std"))]
+fn lock<T>(lock: &Lock<T>) -> impl DerefMut<Target = T> + '_ {
+    lock.borrow_mut()
+}
+
+/// Records of one inference. Array items may be inferred in parallel, therefore they are
+/// behind a lock
+#[derive(Default)]
+pub(crate) struct Recorder {
+    records: Lock<Records>,
+    /// Whether statistics are collected, checked for every value without locking
+    stats: AtomicBool,
+    /// Held during the whole inference, so concurrent inferences with the same settings don't
+    /// mix their records
+    session: Lock<()>,
+}
+
+#[derive(Default)]
+pub(crate) struct Records {
+    /// Conflicting types by JSON Pointer
+    conflicts: BTreeMap<String, Vec<String>>,
+    pub(crate) stats: Collector,
+}
+
+impl Recorder {
+    /// Start a new inference, it lasts until the returned guard is dropped
+    pub(crate) fn start(&self, stats: bool) -> impl Sized + '_ {
+        let session = lock(&self.session);
+        *lock(&self.records) = Records::default();
+        self.stats.store(stats, Ordering::Relaxed);
+        session
+    }
+
+    /// Take the records of the current inference
+    pub(crate) fn finish(&self) -> Records {
+        self.stats.store(false, Ordering::Relaxed);
+        core::mem::take(&mut *lock(&self.records))
+    }
+
+    pub(crate) fn collects_stats(&self) -> bool {
+        self.stats.load(Ordering::Relaxed)
+    }
+
+    /// Record values of the given types at the same location
+    pub(crate) fn conflict(&self, pointer: String, types: Vec<&str>) {
+        let mut records = lock(&self.records);
+        let known = records.conflicts.entry(pointer).or_default();
+        for type_ in types {
+            if !known.iter().any(|known| known == type_) {
+                known.push(type_.into());
+            }
+        }
+    }
+
+    pub(crate) fn observe(&self, observe: impl FnOnce(&mut Collector)) {
+        observe(&mut lock(&self.records).stats)
+    }
+}
+
+impl Records {
+    /// Recorded conflicts sorted by location, their types are sorted by name, as items may be
+    /// inferred in any order
+    pub(crate) fn conflicts(&mut self) -> Vec<TypeConflict> {
+        core::mem::take(&mut self.conflicts)
+            .into_iter()
+            .map(|(pointer, mut types)| {
+                types.sort();
+                TypeConflict { pointer, types }
+            })
+            .collect()
+    }
+}
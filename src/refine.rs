@@ -0,0 +1,203 @@
+//! Refining existing schemas with new samples.
+use alloc::{string::String, vec, vec::Vec};
+use serde_json::{Map, Value};
+
+/// Widen `existing` so it also accepts values described by `inferred`.
+/// Keywords of `existing` that inference doesn't produce, e.g. `title` or `description`,
+/// are preserved.
+pub(crate) fn refine_schema(existing: &Value, inferred: &Value) -> Value {
+    let (existing_map, inferred_map) = match (existing, inferred) {
+        (Value::Object(existing_map), Value::Object(inferred_map)) => (existing_map, inferred_map),
+        // Boolean schemas or other non-object schemas are kept as they are
+        _ => return existing.clone(),
+    };
+    if let Some(branches) = inferred_map.get("anyOf").and_then(Value::as_array) {
+        return branches.iter().fold(existing.clone(), |schema, branch| {
+            refine_schema(&schema, branch)
+        });
+    }
+    if let Some(branches) = existing_map.get("anyOf").and_then(Value::as_array) {
+        return refine_union(existing_map, branches, inferred);
+    }
+    let inferred_type = &inferred["type"];
+    match existing_map.get("type") {
+        // No type restrictions - any value is accepted
+        None => existing.clone(),
+        Some(Value::Array(types)) => {
+            if types.contains(inferred_type) {
+                existing.clone()
+            } else {
+                let mut result = existing_map.clone();
+                let mut types = types.clone();
+                types.push(inferred_type.clone());
+                result.insert("type".into(), Value::Array(types));
+                Value::Object(result)
+            }
+        }
+        Some(existing_type) if existing_type == inferred_type => match inferred_type.as_str() {
+            Some("object") => refine_object(existing_map, inferred_map),
+            Some("array") => refine_array(existing_map, inferred_map),
+            _ => existing.clone(),
+        },
+        Some(existing_type) if existing_type == "number" && inferred_type == "integer" => {
+            existing.clone()
+        }
+        Some(existing_type) if existing_type == "integer" && inferred_type == "number" => {
+            let mut result = existing_map.clone();
+            result.insert("type".into(), inferred_type.clone());
+            Value::Object(result)
+        }
+        Some(_) => {
+            let mut result = Map::new();
+            result.insert(
+                "anyOf".into(),
+                Value::Array(vec![existing.clone(), inferred.clone()]),
+            );
+            Value::Object(result)
+        }
+    }
+}
+
+/// Refine the branch of the same type, or add a new branch
+fn refine_union(existing: &Map<String, Value>, branches: &[Value], inferred: &Value) -> Value {
+    let mut branches = branches.to_vec();
+    if let Some(branch) = branches
+        .iter_mut()
+        .find(|branch| branch["type"] == inferred["type"])
+    {
+        *branch = refine_schema(branch, inferred);
+    } else {
+        branches.push(inferred.clone());
+    }
+    let mut result = existing.clone();
+    result.insert("anyOf".into(), Value::Array(branches));
+    Value::Object(result)
+}
+
+fn refine_object(existing: &Map<String, Value>, inferred: &Map<String, Value>) -> Value {
+    let mut result = existing.clone();
+    let empty = Map::new();
+    let inferred_properties = inferred
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let mut properties = existing
+        .get("properties")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    for (name, inferred_property) in inferred_properties {
+        let refined = match properties.get(name) {
+            Some(existing_property) => refine_schema(existing_property, inferred_property),
+            None => inferred_property.clone(),
+        };
+        properties.insert(name.clone(), refined);
+    }
+    if !properties.is_empty() {
+        result.insert("properties".into(), Value::Object(properties));
+    }
+    // Properties that are absent in the new sample are not required anymore
+    if let Some(required) = existing.get("required").and_then(Value::as_array) {
+        let sample_required = inferred.get("required").and_then(Value::as_array);
+        let required: Vec<Value> = required
+            .iter()
+            .filter(|name| sample_required.is_some_and(|sample| sample.contains(name)))
+            .cloned()
+            .collect();
+        if required.is_empty() {
+            result.remove("required");
+        } else {
+            result.insert("required".into(), Value::Array(required));
+        }
+    }
+    Value::Object(result)
+}
+
+fn refine_array(existing: &Map<String, Value>, inferred: &Map<String, Value>) -> Value {
+    let mut result = existing.clone();
+    // If items are unrestricted in the existing schema, there is nothing to refine
+    if let (Some(existing_items), Some(inferred_items)) =
+        (existing.get("items"), inferred.get("items"))
+    {
+        result.insert(
+            "items".into(),
+            refine_schema(existing_items, inferred_items),
+        );
+    }
+    Value::Object(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::refine;
+    use serde_json::json;
+
+    #[test]
+    fn test_refine() {
+        let existing = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "description": "A user",
+            "properties": {
+                "name": {"type": "string", "description": "Full name"},
+                "age": {"type": "integer"}
+            },
+            "required": ["name", "age"]
+        });
+        let samples = [json!({"name": "foo", "email": "foo@example.com"})];
+        assert_eq!(
+            refine(&existing, &samples),
+            json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "description": "A user",
+                "properties": {
+                    "name": {"type": "string", "description": "Full name"},
+                    "age": {"type": "integer"},
+                    "email": {"type": "string"}
+                },
+                "required": ["name"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_refine_types() {
+        let existing = json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "title": "ID"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "value": {"type": "string"}
+            }
+        });
+        let samples = [json!({"id": 1.5, "tags": ["a", 1], "value": null})];
+        assert_eq!(
+            refine(&existing, &samples),
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "number", "title": "ID"},
+                    "tags": {
+                        "type": "array",
+                        "items": {"anyOf": [{"type": "string"}, {"type": "integer"}]}
+                    },
+                    "value": {"anyOf": [{"type": "string"}, {"type": "null"}]}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_refine_multiple_samples() {
+        let existing = json!({"type": "object", "properties": {}, "required": []});
+        let samples = [json!({"a": 1}), json!({"a": 2, "b": true})];
+        assert_eq!(
+            refine(&existing, &samples),
+            json!({
+                "type": "object",
+                "properties": {"a": {"type": "integer"}, "b": {"type": "boolean"}}
+            })
+        );
+    }
+}
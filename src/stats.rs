@@ -0,0 +1,235 @@
+//! Statistics about the input data and the inferred schema.
+use crate::escape_token;
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Statistics collected during inference.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct InferStats {
+    /// Statistics for each location in the input, keyed by JSON Pointer.
+    /// Array indices are replaced with `*`, so all items of an array share the same entry.
+    pub paths: BTreeMap<String, PathStats>,
+}
+
+/// Statistics for a single location in the input.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PathStats {
+    /// How many values were observed at this location
+    pub samples: usize,
+    /// How many distinct values were observed at this location
+    pub distinct_values: usize,
+    /// Whether the schema for this location is a union of several schemas via `anyOf`
+    pub union: bool,
+    /// How the schemas of a property were merged, for properties of objects observed more than once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merge: Option<MergeStats>,
+}
+
+/// Explanation of how the schemas of a property in several objects were merged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MergeStats {
+    /// How many objects were merged
+    pub objects: usize,
+    /// How many distinct schemas were inferred for the values of this property
+    pub distinct_schemas: usize,
+    /// Why the property is not required, none if it is
+    pub optional: Option<String>,
+}
+
+/// Counts of values and schemas, observed while they are inferred
+#[derive(Default)]
+pub(crate) struct Collector {
+    /// The number of values and their distinct hashes for each location
+    values: BTreeMap<String, (usize, BTreeSet<u64>)>,
+    /// The number of objects at each location
+    objects: BTreeMap<String, usize>,
+    /// The location of the parent object and distinct schema hashes for each property location
+    schemas: BTreeMap<String, (String, BTreeSet<u64>)>,
+}
+
+impl Collector {
+    /// Count a value and, if it is a property of an object at `parent`, the schema inferred for it
+    pub(crate) fn observe(
+        &mut self,
+        path: String,
+        parent: Option<String>,
+        value: u64,
+        schema: u64,
+    ) {
+        if let Some(parent) = parent {
+            self.schemas
+                .entry(path.clone())
+                .or_insert_with(|| (parent, BTreeSet::new()))
+                .1
+                .insert(schema);
+        }
+        let (samples, distinct) = self.values.entry(path).or_default();
+        *samples += 1;
+        distinct.insert(value);
+    }
+
+    pub(crate) fn observe_object(&mut self, path: String) {
+        *self.objects.entry(path).or_default() += 1;
+    }
+
+    /// Statistics for the inferred schema
+    pub(crate) fn finish(self, schema: &Value) -> InferStats {
+        let Collector {
+            values,
+            objects,
+            schemas,
+        } = self;
+        let mut paths: BTreeMap<String, PathStats> = values
+            .into_iter()
+            .map(|(path, (samples, distinct))| {
+                let stats = PathStats {
+                    samples,
+                    distinct_values: distinct.len(),
+                    union: false,
+                    merge: None,
+                };
+                (path, stats)
+            })
+            .collect();
+        for (path, (parent, distinct)) in schemas {
+            let objects = objects[&parent];
+            if objects > 1 {
+                let stats = paths.get_mut(&path).expect("Collected path");
+                let missing = objects - stats.samples;
+                stats.merge = Some(MergeStats {
+                    objects,
+                    distinct_schemas: distinct.len(),
+                    optional: (missing > 0)
+                        .then(|| format!("missing in {} of {} objects", missing, objects)),
+                });
+            }
+        }
+        collect_unions(schema, String::new(), &mut paths);
+        InferStats { paths }
+    }
+}
+
+/// Mark locations in the input, where the inferred schema is a union
+fn collect_unions(schema: &Value, path: String, paths: &mut BTreeMap<String, PathStats>) {
+    if let Some(branches) = schema.get("anyOf").and_then(Value::as_array) {
+        paths.entry(path.clone()).or_default().union = true;
+        for branch in branches {
+            collect_unions(branch, path.clone(), paths);
+        }
+    }
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (key, subschema) in properties {
+            collect_unions(subschema, format!("{}/{}", path, escape_token(key)), paths);
+        }
+    }
+    if let Some(items) = schema.get("items") {
+        collect_unions(items, format!("{}/*", path), paths);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{infer_with_stats, InferError, JSONSchema, MergeStats};
+    use serde_json::json;
+
+    #[test]
+    fn test_stats() {
+        let data = json!({
+            "id": 1,
+            "items": [
+                {"name": "foo", "value": 1},
+                {"name": "bar", "value": "1"},
+                {"name": "foo"}
+            ]
+        });
+        let (_, stats) = infer_with_stats(&data).expect("Inference failed");
+        assert_eq!(
+            serde_json::to_value(&stats).unwrap(),
+            json!({
+              "paths": {
+                "": {"samples": 1, "distinct_values": 1, "union": false},
+                "/id": {"samples": 1, "distinct_values": 1, "union": false},
+                "/items": {"samples": 1, "distinct_values": 1, "union": false},
+                "/items/*": {"samples": 3, "distinct_values": 3, "union": false},
+                "/items/*/name": {
+                    "samples": 3,
+                    "distinct_values": 2,
+                    "union": false,
+                    "merge": {"objects": 3, "distinct_schemas": 1, "optional": null}
+                },
+                "/items/*/value": {
+                    "samples": 2,
+                    "distinct_values": 2,
+                    "union": true,
+                    "merge": {
+                        "objects": 3,
+                        "distinct_schemas": 2,
+                        "optional": "missing in 1 of 3 objects"
+                    }
+                }
+              }
+            })
+        );
+    }
+
+    #[test]
+    fn test_stats_array_union() {
+        let (schema, stats) = infer_with_stats(&json!([1, "a", "b", 1])).expect("Inference failed");
+        assert!(schema["items"]["anyOf"].is_array());
+        let items = &stats.paths["/*"];
+        assert_eq!(items.samples, 4);
+        assert_eq!(items.distinct_values, 3);
+        assert!(items.union);
+        assert!(!stats.paths[""].union);
+    }
+
+    #[test]
+    fn test_merge_explanation() {
+        let data = json!([
+            {"id": 1, "value": 1},
+            {"id": 2, "value": "a"},
+            {"id": 3, "value": null},
+            {"id": 4}
+        ]);
+        let (_, stats) = infer_with_stats(&data).expect("Inference failed");
+        assert_eq!(
+            stats.paths["/*/value"].merge,
+            Some(MergeStats {
+                objects: 4,
+                distinct_schemas: 3,
+                optional: Some("missing in 1 of 4 objects".into()),
+            })
+        );
+        assert_eq!(stats.paths["/*/id"].merge.as_ref().unwrap().optional, None);
+        // A single object is not merged
+        let (_, stats) = infer_with_stats(&json!({"a": 1})).expect("Inference failed");
+        assert_eq!(stats.paths["/a"].merge, None);
+    }
+
+    #[test]
+    fn test_actual_locations() {
+        // Values are inferred once, at their locations, so ignored values have the same schema
+        let data = json!([{"raw": 1}, {"raw": "a"}, {"raw": null}]);
+        let (_, stats) = JSONSchema::new(&data)
+            .ignore_paths(&["/*/raw"])
+            .infer_with_stats()
+            .expect("Inference failed");
+        let raw = &stats.paths["/*/raw"];
+        assert_eq!(raw.samples, 3);
+        assert_eq!(raw.merge.as_ref().unwrap().distinct_schemas, 1);
+    }
+
+    #[test]
+    fn test_error() {
+        let data = json!([1, "a"]);
+        assert!(matches!(
+            JSONSchema::new(&data).strict(true).infer_with_stats(),
+            Err(InferError::TypeConflict { .. })
+        ));
+    }
+}
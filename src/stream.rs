@@ -0,0 +1,172 @@
+//! Inference from JSON text without building `serde_json::Value` for the whole document.
+use crate::{canonical_hash, insert_distinct, set_schema_uri, InferError, JSONSchema, Location};
+use core::fmt;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::Read;
+
+/// Infer a schema for a JSON document read from `reader`, with default settings.
+///
+/// The document is parsed event by event and only scalar values are materialized. Each array
+/// item is replaced by its schema as soon as it is parsed, and equal schemas are kept once, so
+/// memory usage depends on the number of distinct item schemas rather than on the size of the
+/// document. They are merged after the whole array is read, as merging them one by one could
+/// give a different schema. The result is the same as `infer` gives for the parsed document.
+///
+/// ```rust
+/// use infers_jsonschema::{infer, infer_reader};
+/// use serde_json::json;
+///
+/// let input = r#"{"id": 1, "tags": ["a", "b"]}"#;
+/// assert_eq!(
+///     infer_reader(input.as_bytes()).expect("Invalid input"),
+///     infer(&json!({"id": 1, "tags": ["a", "b"]}))
+/// );
+/// ```
+pub fn infer_reader(reader: impl Read) -> Result<Value, InferError> {
+    let schema = JSONSchema::new(&Value::Null);
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let (mut result, _) = schema.recorded(false, || {
+        SchemaSeed(&schema)
+            .deserialize(&mut deserializer)
+            .and_then(|result| deserializer.end().map(|_| result))
+            .map_err(|error| InferError::InvalidInput {
+                message: error.to_string(),
+            })
+    })?;
+    schema.finalize(&mut result);
+    set_schema_uri(&mut result, schema.config.draft.uri().to_owned());
+    Ok(result)
+}
+
+/// The key of the map that represents a number with `serde_json/arbitrary_precision`
+#[cfg(feature = "arbitrary_precision")]
+const NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+/// Produces the schema of the next value in the input instead of the value itself
+struct SchemaSeed<'s, 'a>(&'s JSONSchema<'a>);
+
+impl<'de> DeserializeSeed<'de> for SchemaSeed<'_, '_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl SchemaSeed<'_, '_> {
+    fn scalar<E: de::Error>(&self, value: Value) -> Result<Value, E> {
+        self.0._infer(&value, &Location::Root).map_err(E::custom)
+    }
+}
+
+impl<'de> Visitor<'de> for SchemaSeed<'_, '_> {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        self.scalar(Value::Null)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Value, E> {
+        self.scalar(Value::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Value, E> {
+        self.scalar(value.into())
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value, E> {
+        self.scalar(value.into())
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Value, E> {
+        self.scalar(value.into())
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Value, E> {
+        self.scalar(Value::String(value.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Value, E> {
+        self.scalar(Value::String(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        // Keyed by hashes of item schemas to deduplicate them, as in `JSONSchema::infer_items`.
+        // Arrays of items with many distinct schemas, e.g. objects with unique keys, keep all of
+        // them until the end of the array
+        let mut items = BTreeMap::new();
+        while let Some(item) = seq.next_element_seed(SchemaSeed(self.0))? {
+            insert_distinct(&mut items, canonical_hash(&item), item);
+        }
+        let items = self.0.join_items(
+            items.into_values().flatten().collect(),
+            &[],
+            &Location::Root,
+        );
+        Ok(json!({"type": "array", "items": items}))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        // Repeated keys are described by their last values, as `serde_json::Map` keeps them
+        let mut properties = BTreeMap::new();
+        while let Some(key) = map.next_key::<String>()? {
+            // With `arbitrary_precision`, numbers are passed as maps with their JSON text
+            #[cfg(feature = "arbitrary_precision")]
+            if key == NUMBER_TOKEN && properties.is_empty() {
+                let text: String = map.next_value()?;
+                let number: serde_json::Number = text.parse().map_err(de::Error::custom)?;
+                return self.scalar(Value::Number(number));
+            }
+            let schema = map.next_value_seed(SchemaSeed(self.0))?;
+            properties.insert(key, schema);
+        }
+        let required: Vec<&String> = properties.keys().collect();
+        Ok(json!({"type": "object", "properties": properties, "required": required}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infer;
+
+    #[test]
+    fn test_same_as_materialized() {
+        let document = json!({
+            "id": 42,
+            "name": "foo",
+            "created": "2020-01-01T00:00:00Z",
+            "score": 1.5,
+            "active": true,
+            "parent": null,
+            "empty": [],
+            "users": [
+                {"id": 1, "email": "a@example.com", "tags": ["x"], "address": {"city": "bar"}},
+                {"id": 2, "email": null, "tags": [], "address": {"zip": 12345}},
+                {"id": 3, "email": "c@example.com", "nested": [[1, 2], [3.5]]}
+            ],
+            "mixed": [1, "a", null, {"a": 1}, [true], {"b": "c"}],
+            "matrix": [[[1]], [[2, 3]], []]
+        });
+        let text = serde_json::to_string(&document).unwrap();
+        assert_eq!(infer_reader(text.as_bytes()), Ok(infer(&document)));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            infer_reader(&b"{\"a\": [1, 2"[..]),
+            Err(InferError::InvalidInput { .. })
+        ));
+        assert!(matches!(
+            infer_reader(&b"1 2"[..]),
+            Err(InferError::InvalidInput { .. })
+        ));
+    }
+}
@@ -0,0 +1,764 @@
+//! Inference from TOML documents.
+use crate::{escape_token, InferError, JSONSchema};
+use serde_json::{Map, Number, Value};
+use std::collections::HashSet;
+
+/// The maximum nesting of tables and arrays, as in `serde_json`
+const RECURSION_LIMIT: usize = 128;
+
+/// Infer a schema for a TOML document with default settings.
+/// Offset date-times are converted to RFC 3339 strings and local dates to full dates, so they are
+/// detected as `date-time` and `date` strings. Local date-times and local times have no matching
+/// format and are plain strings
+pub fn infer_toml_str(input: &str) -> Result<Value, InferError> {
+    let document = Parser::new(input).document()?;
+    let schema = JSONSchema::new(&document).try_infer();
+    schema
+}
+
+/// Parses TOML text into JSON values
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+    /// The number of tables and arrays containing the current value
+    depth: usize,
+    /// Pointers to tables defined by headers, they can't be defined again
+    defined: HashSet<String>,
+    /// Pointers to arrays created by `[[...]]` headers, only they can be appended to
+    table_arrays: HashSet<String>,
+}
+
+impl Parser {
+    fn new(input: &str) -> Parser {
+        Parser {
+            chars: input.chars().collect(),
+            position: 0,
+            line: 1,
+            depth: 0,
+            defined: HashSet::new(),
+            table_arrays: HashSet::new(),
+        }
+    }
+
+    fn error(&self, message: &str) -> InferError {
+        InferError::InvalidInput {
+            message: format!("{} at line {}", message, self.line),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        prefix
+            .chars()
+            .enumerate()
+            .all(|(offset, char)| self.chars.get(self.position + offset) == Some(&char))
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.position += 1;
+        if char == '\n' {
+            self.line += 1;
+        }
+        Some(char)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), InferError> {
+        if self.next() == Some(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", expected)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.next();
+        }
+    }
+
+    /// Skip whitespace, comments and newlines, as allowed inside arrays
+    fn skip_blank(&mut self) -> Result<(), InferError> {
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('#') => self.skip_comment()?,
+                Some('\n') => {
+                    self.next();
+                }
+                Some('\r') if self.starts_with("\r\n") => {
+                    self.next();
+                    self.next();
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) -> Result<(), InferError> {
+        while let Some(char) = self.peek() {
+            if char == '\n' || char == '\r' {
+                break;
+            }
+            if char.is_control() && char != '\t' {
+                return Err(self.error("Control character in a comment"));
+            }
+            self.next();
+        }
+        Ok(())
+    }
+
+    /// Expect the end of a line, possibly with a comment
+    fn end_of_line(&mut self) -> Result<(), InferError> {
+        self.skip_whitespace();
+        if self.peek() == Some('#') {
+            self.skip_comment()?;
+        }
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.next();
+                Ok(())
+            }
+            Some('\r') if self.starts_with("\r\n") => {
+                self.next();
+                self.next();
+                Ok(())
+            }
+            _ => Err(self.error("Expected the end of the line")),
+        }
+    }
+
+    fn document(mut self) -> Result<Value, InferError> {
+        let mut root = Map::new();
+        // Keys of the table that key/value pairs are added to
+        let mut current: Vec<String> = vec![];
+        loop {
+            self.skip_blank()?;
+            match self.peek() {
+                None => return Ok(Value::Object(root)),
+                Some('[') if self.starts_with("[[") => {
+                    self.position += 2;
+                    let keys = self.header_keys()?;
+                    self.expect(']')?;
+                    self.expect(']')?;
+                    self.end_of_line()?;
+                    self.append_table(&mut root, &keys)?;
+                    current = keys;
+                }
+                Some('[') => {
+                    self.next();
+                    let keys = self.header_keys()?;
+                    self.expect(']')?;
+                    self.end_of_line()?;
+                    self.define_table(&mut root, &keys)?;
+                    current = keys;
+                }
+                Some(_) => {
+                    let mut pointer = String::new();
+                    let table = descend(&mut root, &current, &mut pointer, true)
+                        .map_err(|message| self.error(message))?;
+                    self.depth = current.len();
+                    self.key_value(table, pointer)?;
+                    self.depth = 0;
+                    self.end_of_line()?;
+                }
+            }
+        }
+    }
+
+    fn header_keys(&mut self) -> Result<Vec<String>, InferError> {
+        self.skip_whitespace();
+        let keys = self.keys()?;
+        self.skip_whitespace();
+        Ok(keys)
+    }
+
+    /// A `[table]` header
+    fn define_table(
+        &mut self,
+        root: &mut Map<String, Value>,
+        keys: &[String],
+    ) -> Result<(), InferError> {
+        let (last, parents) = keys.split_last().expect("Keys are not empty");
+        let mut pointer = String::new();
+        let parent =
+            descend(root, parents, &mut pointer, true).map_err(|message| self.error(message))?;
+        pointer.push('/');
+        pointer.push_str(&escape_token(last));
+        match parent
+            .entry(last.clone())
+            .or_insert_with(|| Value::Object(Map::new()))
+        {
+            Value::Object(_) if self.defined.insert(pointer) => Ok(()),
+            _ => Err(self.error(&format!("Duplicate key '{}'", last))),
+        }
+    }
+
+    /// A `[[table]]` header, which appends a table to an array
+    fn append_table(
+        &mut self,
+        root: &mut Map<String, Value>,
+        keys: &[String],
+    ) -> Result<(), InferError> {
+        let (last, parents) = keys.split_last().expect("Keys are not empty");
+        let mut pointer = String::new();
+        let parent =
+            descend(root, parents, &mut pointer, true).map_err(|message| self.error(message))?;
+        pointer.push('/');
+        pointer.push_str(&escape_token(last));
+        let entry = parent
+            .entry(last.clone())
+            .or_insert_with(|| Value::Array(vec![]));
+        match entry {
+            Value::Array(items) if items.is_empty() || self.table_arrays.contains(&pointer) => {
+                items.push(Value::Object(Map::new()));
+                self.table_arrays.insert(pointer);
+                Ok(())
+            }
+            _ => Err(self.error(&format!("Duplicate key '{}'", last))),
+        }
+    }
+
+    /// A `key = value` pair, where the key may be dotted
+    fn key_value(
+        &mut self,
+        table: &mut Map<String, Value>,
+        mut pointer: String,
+    ) -> Result<(), InferError> {
+        let keys = self.keys()?;
+        self.skip_whitespace();
+        self.expect('=')?;
+        self.skip_whitespace();
+        let (last, parents) = keys.split_last().expect("Keys are not empty");
+        let parent =
+            descend(table, parents, &mut pointer, false).map_err(|message| self.error(message))?;
+        pointer.push('/');
+        pointer.push_str(&escape_token(last));
+        if parent.contains_key(last) {
+            return Err(self.error(&format!("Duplicate key '{}'", last)));
+        }
+        // Dotted keys create nested tables
+        self.depth += parents.len();
+        let value = self.value(pointer)?;
+        self.depth -= parents.len();
+        parent.insert(last.clone(), value);
+        Ok(())
+    }
+
+    /// Dotted keys, e.g. `a."b.c".d`
+    fn keys(&mut self) -> Result<Vec<String>, InferError> {
+        let mut keys = vec![self.key()?];
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('.') {
+                return Ok(keys);
+            }
+            self.next();
+            self.skip_whitespace();
+            keys.push(self.key()?);
+            if self.depth + keys.len() > RECURSION_LIMIT {
+                return Err(self.error("Recursion limit exceeded"));
+            }
+        }
+    }
+
+    /// Enter an array or an inline table, so deeply nested input fails instead of overflowing
+    /// the stack
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Value, InferError>,
+    ) -> Result<Value, InferError> {
+        self.depth += 1;
+        if self.depth > RECURSION_LIMIT {
+            return Err(self.error("Recursion limit exceeded"));
+        }
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn key(&mut self) -> Result<String, InferError> {
+        match self.peek() {
+            Some('"') => {
+                self.next();
+                self.basic_string(false)
+            }
+            Some('\'') => {
+                self.next();
+                self.literal_string(false)
+            }
+            _ => {
+                let start = self.position;
+                while matches!(self.peek(), Some(char) if char.is_ascii_alphanumeric() || char == '_' || char == '-')
+                {
+                    self.next();
+                }
+                if start == self.position {
+                    return Err(self.error("Expected a key"));
+                }
+                Ok(self.chars[start..self.position].iter().collect())
+            }
+        }
+    }
+
+    fn value(&mut self, pointer: String) -> Result<Value, InferError> {
+        match self.peek() {
+            Some('"') if self.starts_with("\"\"\"") => {
+                self.position += 3;
+                self.basic_string(true).map(Value::String)
+            }
+            Some('"') => {
+                self.next();
+                self.basic_string(false).map(Value::String)
+            }
+            Some('\'') if self.starts_with("'''") => {
+                self.position += 3;
+                self.literal_string(true).map(Value::String)
+            }
+            Some('\'') => {
+                self.next();
+                self.literal_string(false).map(Value::String)
+            }
+            Some('[') => {
+                self.next();
+                self.nested(|parser| parser.array(pointer))
+            }
+            Some('{') => {
+                self.next();
+                self.nested(|parser| parser.inline_table(pointer))
+            }
+            Some(_) => self.scalar(pointer),
+            None => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn array(&mut self, pointer: String) -> Result<Value, InferError> {
+        let mut items = vec![];
+        loop {
+            self.skip_blank()?;
+            if self.peek() == Some(']') {
+                self.next();
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value(format!("{}/{}", pointer, items.len()))?);
+            self.skip_blank()?;
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn inline_table(&mut self, pointer: String) -> Result<Value, InferError> {
+        let mut table = Map::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(Value::Object(table));
+        }
+        loop {
+            self.skip_whitespace();
+            self.key_value(&mut table, pointer.clone())?;
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Object(table)),
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+
+    /// A basic string after its opening quotes, with escapes
+    fn basic_string(&mut self, multiline: bool) -> Result<String, InferError> {
+        let mut string = String::new();
+        if multiline {
+            self.skip_first_newline();
+        }
+        loop {
+            match self.next() {
+                None => return Err(self.error("Unterminated string")),
+                Some('"') if !multiline => return Ok(string),
+                Some('"') if self.starts_with("\"\"") => {
+                    self.position += 2;
+                    // Up to two quotes before the closing ones belong to the string
+                    for _ in 0..2 {
+                        if self.peek() == Some('"') {
+                            self.next();
+                            string.push('"');
+                        }
+                    }
+                    return Ok(string);
+                }
+                Some('\\') => {
+                    if multiline && self.line_ending_backslash() {
+                        continue;
+                    }
+                    string.push(self.escape()?);
+                }
+                Some('\n') if !multiline => return Err(self.error("Newline in a string")),
+                Some(char) => {
+                    if char.is_control() && !matches!(char, '\t' | '\n' | '\r') {
+                        return Err(self.error("Control character in a string"));
+                    }
+                    string.push(char)
+                }
+            }
+        }
+    }
+
+    /// A line ending backslash in a multiline string trims the following whitespace
+    fn line_ending_backslash(&mut self) -> bool {
+        let start = (self.position, self.line);
+        self.skip_whitespace();
+        if !matches!(self.peek(), Some('\n') | Some('\r')) {
+            self.position = start.0;
+            self.line = start.1;
+            return false;
+        }
+        while matches!(
+            self.peek(),
+            Some(' ') | Some('\t') | Some('\n') | Some('\r')
+        ) {
+            self.next();
+        }
+        true
+    }
+
+    fn escape(&mut self) -> Result<char, InferError> {
+        let digits = match self.next() {
+            Some('b') => return Ok('\u{8}'),
+            Some('t') => return Ok('\t'),
+            Some('n') => return Ok('\n'),
+            Some('f') => return Ok('\u{c}'),
+            Some('r') => return Ok('\r'),
+            Some('"') => return Ok('"'),
+            Some('\\') => return Ok('\\'),
+            Some('u') => 4,
+            Some('U') => 8,
+            _ => return Err(self.error("Invalid escape sequence")),
+        };
+        let end = self.position + digits;
+        let code: String = self
+            .chars
+            .get(self.position..end)
+            .ok_or_else(|| self.error("Invalid escape sequence"))?
+            .iter()
+            .collect();
+        self.position = end;
+        u32::from_str_radix(&code, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error("Invalid escape sequence"))
+    }
+
+    /// A literal string after its opening quotes, without escapes
+    fn literal_string(&mut self, multiline: bool) -> Result<String, InferError> {
+        let mut string = String::new();
+        if multiline {
+            self.skip_first_newline();
+        }
+        loop {
+            match self.next() {
+                None => return Err(self.error("Unterminated string")),
+                Some('\'') if !multiline => return Ok(string),
+                Some('\'') if self.starts_with("''") => {
+                    self.position += 2;
+                    for _ in 0..2 {
+                        if self.peek() == Some('\'') {
+                            self.next();
+                            string.push('\'');
+                        }
+                    }
+                    return Ok(string);
+                }
+                Some('\n') if !multiline => return Err(self.error("Newline in a string")),
+                Some(char) => string.push(char),
+            }
+        }
+    }
+
+    /// A newline right after the opening quotes of a multiline string is not a part of it
+    fn skip_first_newline(&mut self) {
+        if self.peek() == Some('\n') {
+            self.next();
+        } else if self.starts_with("\r\n") {
+            self.next();
+            self.next();
+        }
+    }
+
+    /// Booleans, numbers, dates and times
+    fn scalar(&mut self, pointer: String) -> Result<Value, InferError> {
+        let start = self.position;
+        while let Some(char) = self.peek() {
+            let is_date_separator = char == ' '
+                && self.position - start == 10
+                && matches!(self.chars.get(self.position + 1), Some(next) if next.is_ascii_digit());
+            if char.is_ascii_alphanumeric() || "+-_.:".contains(char) || is_date_separator {
+                self.next();
+            } else {
+                break;
+            }
+        }
+        let token: String = self.chars[start..self.position].iter().collect();
+        match token.as_str() {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            "inf" | "+inf" | "-inf" | "nan" | "+nan" | "-nan" => {
+                return Err(InferError::NonFiniteNumber { pointer })
+            }
+            _ => {}
+        }
+        if let Some(value) = date_time(&token) {
+            return Ok(Value::String(value));
+        }
+        number(&token).ok_or_else(|| self.error(&format!("Invalid value '{}'", token)))
+    }
+}
+
+/// Go to the table with the given keys, creating missing ones. Arrays of tables lead to their
+/// last table. If `headers` is set, tables may be extended, otherwise keys are dotted keys of
+/// a key/value pair, which can't extend tables defined by headers
+fn descend<'m>(
+    mut table: &'m mut Map<String, Value>,
+    keys: &[String],
+    pointer: &mut String,
+    headers: bool,
+) -> Result<&'m mut Map<String, Value>, &'static str> {
+    for key in keys {
+        pointer.push('/');
+        pointer.push_str(&escape_token(key));
+        let entry = table
+            .entry(key.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        table = match entry {
+            Value::Object(nested) => nested,
+            Value::Array(items) if headers => {
+                let idx = items.len().saturating_sub(1);
+                match items.last_mut() {
+                    Some(Value::Object(nested)) => {
+                        pointer.push_str(&format!("/{}", idx));
+                        nested
+                    }
+                    _ => return Err("Key is not a table"),
+                }
+            }
+            _ => return Err("Key is not a table"),
+        };
+    }
+    Ok(table)
+}
+
+/// Offset date-times as RFC 3339 strings and other dates and times as they are written
+fn date_time(token: &str) -> Option<String> {
+    let bytes = token.as_bytes();
+    let is_digits = |range: core::ops::Range<usize>| {
+        bytes.len() >= range.end && bytes[range].iter().all(u8::is_ascii_digit)
+    };
+    let is_date = is_digits(0..4)
+        && bytes.get(4) == Some(&b'-')
+        && is_digits(5..7)
+        && bytes.get(7) == Some(&b'-')
+        && is_digits(8..10);
+    let is_time = is_digits(0..2) && bytes.get(2) == Some(&b':') && is_digits(3..5);
+    if is_time {
+        return Some(token.to_owned());
+    }
+    if !is_date {
+        return None;
+    }
+    if bytes.len() == 10 {
+        return Some(token.to_owned());
+    }
+    if !matches!(bytes[10], b'T' | b't' | b' ') {
+        return None;
+    }
+    // `T`, `t` or a space separate the date and the time, RFC 3339 prefers `T`
+    let mut normalized = format!("{}T{}", &token[..10], &token[11..]);
+    if normalized.ends_with('z') {
+        normalized.pop();
+        normalized.push('Z');
+    }
+    Some(normalized)
+}
+
+/// Integers in any base and floats, with underscores between digits
+fn number(token: &str) -> Option<Value> {
+    let chars: Vec<char> = token.chars().collect();
+    let underscores_are_valid = chars.iter().enumerate().all(|(idx, &char)| {
+        char != '_'
+            || (idx > 0
+                && chars[idx - 1].is_ascii_hexdigit()
+                && chars.get(idx + 1).is_some_and(char::is_ascii_hexdigit))
+    });
+    if !underscores_are_valid {
+        return None;
+    }
+    let digits = token.replace('_', "");
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(rest) = digits.strip_prefix(prefix) {
+            if rest.starts_with(['+', '-']) {
+                return None;
+            }
+            return i64::from_str_radix(rest, radix)
+                .ok()
+                .map(|integer| Value::Number(integer.into()));
+        }
+    }
+    let unsigned = digits.trim_start_matches(['+', '-']);
+    if unsigned.len() + 1 < digits.len()
+        || !unsigned.starts_with(|char: char| char.is_ascii_digit())
+    {
+        return None;
+    }
+    // Leading zeros are not allowed
+    let integral = unsigned.split(['.', 'e', 'E']).next().unwrap_or_default();
+    if integral.len() > 1 && integral.starts_with('0') {
+        return None;
+    }
+    if unsigned.chars().all(|char| char.is_ascii_digit()) {
+        return digits
+            .parse::<i64>()
+            .ok()
+            .map(|integer| Value::Number(integer.into()));
+    }
+    // A dot must be followed by digits
+    if unsigned.contains(".e") || unsigned.contains(".E") || unsigned.ends_with('.') {
+        return None;
+    }
+    digits
+        .parse::<f64>()
+        .ok()
+        .and_then(Number::from_f64)
+        .map(Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(input: &str) -> Result<Value, InferError> {
+        Parser::new(input).document()
+    }
+
+    #[test]
+    fn test_infer() {
+        let input = r#"
+# Deployment settings
+title = "Example"
+created = 1979-05-27T07:32:00-08:00
+
+[server]
+host = "example.com"
+ports = [8000, 8001]
+enabled = true
+
+[[users]]
+name = "alice"
+since = 2020-01-01
+
+[[users]]
+name = "bob"
+"#;
+        let schema = infer_toml_str(input).unwrap();
+        assert_eq!(
+            schema["properties"]["created"],
+            json!({"type": "string", "format": "date-time"})
+        );
+        assert_eq!(
+            schema["properties"]["server"]["properties"]["ports"],
+            json!({"type": "array", "items": {"type": "integer"}})
+        );
+        let users = &schema["properties"]["users"]["items"];
+        assert_eq!(users["required"], json!(["name"]));
+        assert_eq!(
+            users["properties"]["since"],
+            json!({"type": "string", "format": "date"})
+        );
+    }
+
+    #[test]
+    fn test_values() {
+        let input = r#"
+a.b = 'literal \n'
+a."c.d" = { x = 1, y.z = [] }
+int = +1_000
+hex = 0xff
+float = 6.626e-34
+multiline = """
+one \
+  two"""
+literal = '''
+it's'''
+local = 1979-05-27 07:32:00
+time = 07:32:00
+offset = 1979-05-27 07:32:00z
+"#;
+        assert_eq!(
+            parse(input).unwrap(),
+            json!({
+                "a": {"b": "literal \\n", "c.d": {"x": 1, "y": {"z": []}}},
+                "int": 1000,
+                "hex": 255,
+                "float": 6.626e-34,
+                "multiline": "one two",
+                "literal": "it's",
+                "local": "1979-05-27T07:32:00",
+                "time": "07:32:00",
+                "offset": "1979-05-27T07:32:00Z"
+            })
+        );
+    }
+
+    #[test]
+    fn test_non_finite() {
+        assert_eq!(
+            infer_toml_str("[a]\nb = [1.0, nan]"),
+            Err(InferError::NonFiniteNumber {
+                pointer: "/a/b/1".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        let arrays = |depth| format!("a = {}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(infer_toml_str(&arrays(RECURSION_LIMIT)).is_ok());
+        let tables = format!("a = {}{}", "{b = ".repeat(200_000), "}".repeat(200_000));
+        let keys = format!("{} = 1", vec!["a"; 200_000].join("."));
+        let header = format!("[{}]", vec!["a"; 200_000].join("."));
+        for input in [arrays(200_000), tables, keys, header] {
+            assert!(matches!(
+                parse(&input),
+                Err(InferError::InvalidInput { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        for input in [
+            "a = 1\na = 2",
+            "[a]\n[a]",
+            "a = 1\n[a]",
+            "a = 01",
+            "a = 1__0",
+            "a = \"unterminated",
+            "a = 1 b = 2",
+            "a = [1 2]",
+        ] {
+            assert!(
+                matches!(parse(input), Err(InferError::InvalidInput { .. })),
+                "{}",
+                input
+            );
+        }
+    }
+}
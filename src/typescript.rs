@@ -0,0 +1,296 @@
+//! Translation of inferred schemas to TypeScript type definitions.
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use serde_json::{Map, Value};
+
+/// Generate TypeScript declarations for an inferred JSON Schema.
+///
+/// Object schemas with properties become interfaces named after the root name and the path to
+/// them, e.g. `RootAddress` for the `address` property of `Root`, and the root interface goes
+/// first. Properties that are not required are optional, unions become `A | B` and arrays
+/// become `T[]`. A root schema that is not an object is declared as a type alias.
+///
+/// ```rust
+/// use infers_jsonschema::{infer, to_typescript};
+/// use serde_json::json;
+///
+/// let schema = infer(&json!({"id": 1, "tags": ["a"]}));
+/// assert_eq!(
+///     to_typescript(&schema, "Item"),
+///     "export interface Item {\n  id: number;\n  tags: string[];\n}\n"
+/// );
+/// ```
+pub fn to_typescript(schema: &Value, root_name: &str) -> String {
+    let mut emitter = Emitter {
+        root: schema,
+        names: BTreeSet::new(),
+        references: BTreeMap::new(),
+        declarations: Vec::new(),
+    };
+    let name = pascal_case(root_name);
+    // The root may be referenced by recursive schemas
+    if is_interface(schema) {
+        let name = emitter.unique_name(&name);
+        emitter.references.insert("#".into(), name.clone());
+        emitter.interface(schema, name);
+    } else {
+        // Interfaces of nested objects are declared after the alias
+        emitter.declarations.push(String::new());
+        let type_ = emitter.type_of(schema, &name);
+        emitter.declarations[0] = format!("export type {} = {};\n", name, type_);
+    }
+    emitter.declarations.join("\n")
+}
+
+struct Emitter<'a> {
+    root: &'a Value,
+    /// Interface names that are already used
+    names: BTreeSet<String>,
+    /// Names of interfaces that were generated from referenced schemas
+    references: BTreeMap<String, String>,
+    declarations: Vec<String>,
+}
+
+impl Emitter<'_> {
+    /// Declare an interface with a unique name and return the name
+    fn interface(&mut self, schema: &Value, name: String) -> String {
+        // Nested interfaces are declared after this one
+        let index = self.declarations.len();
+        self.declarations.push(String::new());
+        let empty = Map::new();
+        let properties = schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .unwrap_or(&empty);
+        let required = schema.get("required").and_then(Value::as_array);
+        let mut declaration = format!("export interface {} {{\n", name);
+        for (property, subschema) in properties {
+            let type_ = self.type_of(subschema, &format!("{}{}", name, pascal_case(property)));
+            let optional =
+                !required.is_some_and(|required| required.contains(&property.as_str().into()));
+            declaration.push_str(&format!(
+                "  {}{}: {};\n",
+                property_name(property),
+                if optional { "?" } else { "" },
+                type_
+            ));
+        }
+        declaration.push_str("}\n");
+        self.declarations[index] = declaration;
+        name
+    }
+
+    fn type_of(&mut self, schema: &Value, name: &str) -> String {
+        let map = match schema {
+            Value::Object(map) => map,
+            Value::Bool(false) => return "never".into(),
+            _ => return "unknown".into(),
+        };
+        if let Some(Value::String(reference)) = map.get("$ref") {
+            return self.reference(reference, name);
+        }
+        if let Some(Value::Array(values)) = map.get("enum") {
+            return union(values.iter().map(literal).collect());
+        }
+        if let Some(value) = map.get("const") {
+            return literal(value);
+        }
+        if let Some(Value::Array(branches)) = map.get("anyOf").or_else(|| map.get("oneOf")) {
+            let types = branches
+                .iter()
+                .map(|branch| self.type_of(branch, name))
+                .collect();
+            return union(types);
+        }
+        match map.get("type") {
+            Some(Value::String(type_)) => self.type_name(schema, type_, name),
+            Some(Value::Array(types)) => {
+                let types = types
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|type_| self.type_name(schema, type_, name))
+                    .collect();
+                union(types)
+            }
+            _ => "unknown".into(),
+        }
+    }
+
+    fn type_name(&mut self, schema: &Value, type_: &str, name: &str) -> String {
+        match type_ {
+            "null" => "null".into(),
+            "boolean" => "boolean".into(),
+            "integer" | "number" => "number".into(),
+            "string" => "string".into(),
+            "array" => {
+                let items = match schema.get("items") {
+                    Some(items) if !items.is_array() => {
+                        self.type_of(items, &format!("{}Item", name))
+                    }
+                    _ => "unknown".into(),
+                };
+                if items.contains(" | ") {
+                    format!("({})[]", items)
+                } else {
+                    format!("{}[]", items)
+                }
+            }
+            _ if is_interface(schema) => {
+                let name = self.unique_name(name);
+                self.interface(schema, name)
+            }
+            _ => match schema.get("additionalProperties") {
+                Some(values @ Value::Object(_)) => {
+                    let values = self.type_of(values, &format!("{}Value", name));
+                    format!("Record<string, {}>", values)
+                }
+                _ => "Record<string, unknown>".into(),
+            },
+        }
+    }
+
+    /// Referenced object schemas, e.g. recursive ones, are declared once
+    fn reference(&mut self, reference: &str, name: &str) -> String {
+        if let Some(known) = self.references.get(reference) {
+            return known.clone();
+        }
+        let root = self.root;
+        match root.pointer(reference.trim_start_matches('#')) {
+            Some(target) if is_interface(target) => {
+                // The name is known before the interface is generated, as it may refer to itself
+                let name = self.unique_name(name);
+                self.references.insert(reference.to_string(), name.clone());
+                self.interface(target, name)
+            }
+            Some(target) => self.type_of(target, name),
+            None => "unknown".into(),
+        }
+    }
+
+    fn unique_name(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut suffix = 1;
+        while !self.names.insert(candidate.clone()) {
+            suffix += 1;
+            candidate = format!("{}{}", name, suffix);
+        }
+        candidate
+    }
+}
+
+/// Object schemas with properties become interfaces
+fn is_interface(schema: &Value) -> bool {
+    schema.get("properties").is_some_and(Value::is_object)
+        && schema.get("type").is_none_or(|type_| type_ == "object")
+}
+
+/// Join types, with `null` last as is customary, e.g. `number | null`
+fn union(mut types: Vec<String>) -> String {
+    let mut seen = BTreeSet::new();
+    types.retain(|type_| seen.insert(type_.clone()));
+    if let Some(position) = types.iter().position(|type_| type_ == "null") {
+        let null = types.remove(position);
+        types.push(null);
+    }
+    match types.len() {
+        0 => "never".into(),
+        _ => types.join(" | "),
+    }
+}
+
+/// TypeScript literal types for scalar values. JSON strings are valid TypeScript strings
+fn literal(value: &Value) -> String {
+    match value {
+        Value::Array(_) => "unknown[]".into(),
+        Value::Object(_) => "Record<string, unknown>".into(),
+        value => value.to_string(),
+    }
+}
+
+/// Property names that are not identifiers are quoted
+fn property_name(name: &str) -> String {
+    let is_identifier = name
+        .starts_with(|char: char| char.is_ascii_alphabetic() || "_$".contains(char))
+        && name
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || "_$".contains(char));
+    if is_identifier {
+        name.to_string()
+    } else {
+        Value::String(name.to_string()).to_string()
+    }
+}
+
+/// Convert a name to an identifier in PascalCase, e.g. `user-name` to `UserName`
+fn pascal_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for word in name.split(|char: char| !char.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            result.push(first.to_ascii_uppercase());
+            result.extend(chars);
+        }
+    }
+    if !result.starts_with(|char: char| char.is_ascii_alphabetic()) {
+        result.insert(0, '_');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infer, JSONSchema};
+    use serde_json::json;
+
+    #[test]
+    fn test_nested() {
+        let data = json!([
+            {"id": 1, "user-name": "foo", "address": {"city": "bar"}, "tags": ["a"], "score": null},
+            {"id": 2, "user-name": "baz", "address": {"city": "qux"}, "score": 1.5}
+        ]);
+        assert_eq!(
+            to_typescript(&infer(&data), "users"),
+            "export type Users = UsersItem[];\n\
+             \n\
+             export interface UsersItem {\n  \
+               address: UsersItemAddress;\n  \
+               id: number;\n  \
+               score: number | null;\n  \
+               tags?: string[];\n  \
+               \"user-name\": string;\n\
+             }\n\
+             \n\
+             export interface UsersItemAddress {\n  \
+               city: string;\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_recursive() {
+        let data = json!({"value": 1, "child": {"value": 2, "child": {"value": 3}}});
+        let schema = JSONSchema::new(&data).detect_recursion(true).infer();
+        assert_eq!(
+            to_typescript(&schema, "Node"),
+            "export interface Node {\n  child?: Node;\n  value: number;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(pascal_case("user-name"), "UserName");
+        assert_eq!(pascal_case("1st"), "_1st");
+        assert_eq!(property_name("$id"), "$id");
+        assert_eq!(property_name("a b"), "\"a b\"");
+        let schema = json!({"enum": ["a", 1, null]});
+        assert_eq!(
+            to_typescript(&schema, "Kind"),
+            "export type Kind = \"a\" | 1 | null;\n"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Traversal of sub-schemas.
+use serde_json::Value;
+
+/// Keywords whose values are schemas
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "additionalProperties",
+    "propertyNames",
+    "contains",
+    "not",
+    "if",
+    "then",
+    "else",
+    "contentSchema",
+];
+/// Keywords whose values are arrays of schemas
+const SCHEMA_ARRAY_KEYWORDS: &[&str] = &["anyOf", "oneOf", "allOf", "prefixItems"];
+/// Keywords whose values are objects with schemas as values
+const SCHEMA_MAP_KEYWORDS: &[&str] = &["properties", "patternProperties", "$defs", "definitions"];
+
+/// Call `visit` on every sub-schema of `schema` and then on `schema` itself.
+/// Values of keywords that are not schemas, e.g. `enum` or `default`, are not visited
+pub(crate) fn walk_mut(schema: &mut Value, visit: &mut dyn FnMut(&mut Value)) {
+    subschemas_mut(schema, &mut |subschema| walk_mut(subschema, visit));
+    visit(schema)
+}
+
+/// Call `visit` on `schema` and then on its sub-schemas, unless `visit` returns `false`
+pub(crate) fn walk_top_down_mut(schema: &mut Value, visit: &mut dyn FnMut(&mut Value) -> bool) {
+    if visit(schema) {
+        subschemas_mut(schema, &mut |subschema| walk_top_down_mut(subschema, visit));
+    }
+}
+
+/// Call `visit` on direct sub-schemas of `schema`
+pub(crate) fn subschemas_mut(schema: &mut Value, visit: &mut dyn FnMut(&mut Value)) {
+    if let Value::Object(map) = schema {
+        for (keyword, value) in map.iter_mut() {
+            let keyword = keyword.as_str();
+            if SCHEMA_KEYWORDS.contains(&keyword) {
+                visit(value);
+            } else if keyword == "items" {
+                // `items` is either a single schema or a tuple of schemas in older drafts
+                match value {
+                    Value::Array(items) => items.iter_mut().for_each(&mut *visit),
+                    _ => visit(value),
+                }
+            } else if SCHEMA_ARRAY_KEYWORDS.contains(&keyword) {
+                if let Value::Array(schemas) = value {
+                    schemas.iter_mut().for_each(&mut *visit);
+                }
+            } else if SCHEMA_MAP_KEYWORDS.contains(&keyword) {
+                if let Value::Object(schemas) = value {
+                    schemas.values_mut().for_each(&mut *visit);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_walk_skips_values() {
+        let mut schema = json!({
+            "type": "object",
+            "properties": {"type": {"type": "string", "default": {"type": "x"}}},
+            "items": [{"type": "integer"}],
+            "anyOf": [{"type": "null"}],
+            "enum": [{"type": "y"}]
+        });
+        let mut visited = vec![];
+        walk_mut(&mut schema, &mut |schema| {
+            visited.push(schema["type"].clone())
+        });
+        visited.sort_by_key(|value| value.to_string());
+        assert_eq!(
+            visited,
+            vec![
+                json!("integer"),
+                json!("null"),
+                json!("object"),
+                json!("string")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_walk_top_down_stops() {
+        let mut schema = json!({
+            "type": "object",
+            "properties": {"a": {"type": "array", "items": {"type": "string"}}}
+        });
+        let mut visited = vec![];
+        walk_top_down_mut(&mut schema, &mut |schema| {
+            visited.push(schema["type"].clone());
+            schema["type"] != "array"
+        });
+        assert_eq!(visited, vec![json!("object"), json!("array")]);
+    }
+}